@@ -0,0 +1,841 @@
+// The `atop` command line: argument parsing, the text report, `doctor` and `debug`
+use crate::metrics::{self, InterfaceFilter, ProcessMetrics, Scenario};
+use crate::ui;
+use crate::{Sampler, SamplerOptions};
+use std::env;
+
+fn run_dashboard(options: SamplerOptions) {
+    let source =
+        ui::source::CollectorSource::new(options).map_err(|e| std::io::Error::other(e.to_string()));
+    let mut dashboard = match source.and_then(ui::Dashboard::with_source) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("Error initializing dashboard: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = dashboard.run() {
+        eprintln!("Error running dashboard: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn print_usage() {
+    eprintln!("Usage: atop [OPTIONS]");
+    eprintln!();
+    eprintln!("System memory metrics monitoring tool");
+    eprintln!();
+    eprintln!(
+        "When run without arguments, launches an interactive dashboard; --dashboard launches it with the options below."
+    );
+    eprintln!();
+    eprintln!("OPTIONS:");
+    eprintln!("    --json               Output as JSON");
+    eprintln!("    --dashboard          Launch the dashboard with the given options");
+    eprintln!(
+        "    --sample, -s <N>     Number of samples to collect (0 = infinite, only with --json)"
+    );
+    eprintln!("    --interval, -i <MS>  Update interval in milliseconds (default: 1000, min: 100)");
+    eprintln!(
+        "    --summary            Print a run summary (throttling) after the last sample (only with --sample)"
+    );
+    eprintln!(
+        "    --interfaces <LIST>  Network interfaces to report, e.g. en0,utun* (default: all but loopback)"
+    );
+    eprintln!(
+        "    --cgroup-root <PATH> cgroup v2 hierarchy to aggregate (default: /sys/fs/cgroup)"
+    );
+    eprintln!(
+        "    --fs-warn <PERCENT>  Flag filesystems with more space or inodes used (default: 90)"
+    );
+    eprintln!(
+        "    --synthetic          Generate memory, power and frequency data instead of reading the system"
+    );
+    eprintln!(
+        "    --scenario <FILE>    Waveforms for --synthetic from a JSON scenario file (implies --synthetic)"
+    );
+    eprintln!("    --help               Print this help message");
+    eprintln!();
+    eprintln!("COMMANDS:");
+    eprintln!(
+        "    doctor [--json] [--cgroup-root <PATH>] Check every data source and print a pass/warn/fail report"
+    );
+    eprintln!();
+    eprintln!("DEBUG COMMANDS:");
+    eprintln!(
+        "    debug dump-channels [-o FILE] [-i MS]  Write every IOReport channel delta to JSON (default: ioreport-dump.json)"
+    );
+    eprintln!(
+        "    debug replay <FILE>                    Run a channel dump through the power and performance parsers"
+    );
+    eprintln!(
+        "    debug chip-profile [CHIP]              Print the chip profile in effect, e.g. for \"Apple M2 Max\""
+    );
+    eprintln!();
+    eprintln!("DASHBOARD CONTROLS:");
+    eprintln!("    +/-                  Adjust refresh rate");
+    eprintln!("    </>, r               Change process sort column, reverse order");
+    eprintln!("    /                    Filter processes (e.g. `user:ci cpu>10 mem>500M`)");
+    eprintln!("    t                    Toggle process tree view");
+    eprintln!("    Up/Down, PgUp/PgDn   Select a process");
+    eprintln!("    Space, Left/Right    Collapse/expand the selected process in tree view");
+    eprintln!("    p                    Pin the selected process to the top");
+    eprintln!(
+        "    k/K, s/c             Send SIGTERM/SIGKILL, SIGSTOP/SIGCONT to the selected process"
+    );
+    eprintln!("    n/N                  Raise/lower the nice value of the selected process");
+    eprintln!("    g                    Toggle the cgroup v2 group table (Linux)");
+    eprintln!("    q/ESC                Quit (ESC clears an active filter first)");
+}
+
+// Same abbreviations as the `top` CPU line
+fn format_cpu_times(times: &metrics::cpu_time::CpuTimes) -> String {
+    let mut parts = vec![
+        format!("us {:.1}%", times.user),
+        format!("sy {:.1}%", times.system),
+        format!("ni {:.1}%", times.nice),
+        format!("id {:.1}%", times.idle),
+    ];
+    let extended = [
+        ("wa", times.iowait),
+        ("hi", times.irq),
+        ("si", times.softirq),
+        ("st", times.steal),
+    ];
+    for (label, value) in extended {
+        if let Some(value) = value {
+            parts.push(format!("{} {:.1}%", label, value));
+        }
+    }
+    parts.join(", ")
+}
+
+// Probe every data source and print a pass/warn/fail report; Ok(false) when a check failed
+fn run_doctor(args: &[String]) -> Result<bool, String> {
+    use metrics::doctor::CheckStatus;
+
+    let mut json = false;
+    let mut cgroup_root = metrics::DEFAULT_CGROUP_ROOT.to_string();
+    let mut i = 0;
+    while i < args.len() {
+        match (args[i].as_str(), args.get(i + 1)) {
+            ("--json", _) => json = true,
+            ("--cgroup-root", Some(path)) => {
+                cgroup_root = path.clone();
+                i += 1;
+            }
+            ("--cgroup-root", None) => return Err("--cgroup-root requires a path".to_string()),
+            (other, _) => return Err(format!("Unexpected argument '{}'", other)),
+        }
+        i += 1;
+    }
+    let checks = metrics::doctor::run_checks(&cgroup_root);
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let failed = count(CheckStatus::Fail);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&checks).unwrap());
+        return Ok(failed == 0);
+    }
+
+    println!(
+        "atop {} on {} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    for check in &checks {
+        let status = match check.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!(
+            "{}  {:<12} {:<48} {:>8.2} ms  {}",
+            status, check.area, check.probe, check.elapsed_ms, check.detail
+        );
+        if let Some(ref hint) = check.hint {
+            println!("      hint: {}", hint);
+        }
+    }
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        failed
+    );
+    Ok(failed == 0)
+}
+
+// `atop debug ...` subcommands for chip support reports
+fn run_debug(args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        Some("dump-channels") => {
+            let mut output = "ioreport-dump.json".to_string();
+            let mut interval_ms: u64 = 1000;
+            let mut i = 1;
+            while i < args.len() {
+                let value = args.get(i + 1);
+                match (args[i].as_str(), value) {
+                    ("--output" | "-o", Some(path)) => output = path.clone(),
+                    ("--interval" | "-i", Some(ms)) => {
+                        interval_ms = ms
+                            .parse()
+                            .map_err(|_| format!("Invalid interval '{}'", ms))?
+                    }
+                    (arg @ ("--output" | "-o" | "--interval" | "-i"), None) => {
+                        return Err(format!("{} requires a value", arg));
+                    }
+                    (arg, _) => return Err(format!("Unexpected argument '{}'", arg)),
+                }
+                i += 2;
+            }
+
+            let dump = metrics::capture_channel_dump(interval_ms)
+                .map_err(|e| format!("Error capturing IOReport channels: {}", e))?;
+            dump.save(&output).map_err(|e| e.to_string())?;
+            eprintln!(
+                "Wrote {} channels ({} ms) to {}",
+                dump.sample.channels.len(),
+                dump.sample.duration_ms,
+                output
+            );
+            Ok(())
+        }
+        Some("replay") => {
+            let path = args.get(1).ok_or("replay requires a dump file")?;
+            let dump = metrics::ChannelDump::load(path).map_err(|e| e.to_string())?;
+            // Local overrides apply, so a fixed-up profile can be checked against the dump
+            let profiles = metrics::ChipProfiles::load().map_err(|e| e.to_string())?;
+            let (profile, power, performance) =
+                dump.replay_with(&profiles).map_err(|e| e.to_string())?;
+            let json = serde_json::json!({
+                "chip": dump.chip,
+                "profile": profile.name,
+                "power": power,
+                "performance": performance,
+            });
+            println!("{}", json);
+            Ok(())
+        }
+        Some("chip-profile") => {
+            // Defaults to this machine's CPU brand
+            let chip = match args.get(1) {
+                Some(chip) => chip.clone(),
+                None => metrics::get_cpu_metrics()
+                    .map(|cpu| cpu.cpu_brand)
+                    .unwrap_or_default(),
+            };
+            let profile = metrics::ChipProfiles::load()
+                .map_err(|e| e.to_string())?
+                .select(&chip);
+            println!("{}", serde_json::to_string_pretty(&profile).unwrap());
+            Ok(())
+        }
+        Some(other) => Err(format!("Unknown debug command '{}'", other)),
+        None => Err("debug requires a command (dump-channels, replay, chip-profile)".to_string()),
+    }
+}
+
+/// Entry point of the `atop` binary
+pub fn run() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.get(1).map(String::as_str) == Some("doctor") {
+        match run_doctor(&args[2..]) {
+            Ok(healthy) => std::process::exit(if healthy { 0 } else { 1 }),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    if args.get(1).map(String::as_str) == Some("debug") {
+        if let Err(e) = run_debug(&args[2..]) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // If no arguments provided, launch the dashboard
+    if args.len() == 1 {
+        run_dashboard(SamplerOptions::default());
+        return;
+    }
+
+    // Parse arguments
+    let mut json_output = false;
+    let mut dashboard = false;
+    let mut print_summary = false;
+    let mut interface_filter = InterfaceFilter::default();
+    let mut cgroup_root = metrics::DEFAULT_CGROUP_ROOT.to_string();
+    let mut fs_warn_percent = metrics::DEFAULT_FS_WARN_PERCENT;
+    let mut scenario: Option<Scenario> = None;
+    let mut sample_count: Option<u32> = None;
+    let mut interval_ms: u32 = 1000; // Default 1 second
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json_output = true,
+            "--dashboard" => dashboard = true,
+            "--summary" => print_summary = true,
+            "--sample" | "-s" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<u32>() {
+                        Ok(n) => {
+                            sample_count = Some(n);
+                            i += 1; // Skip the next argument since we consumed it
+                        }
+                        Err(_) => {
+                            eprintln!("Error: Invalid sample count '{}'", args[i + 1]);
+                            std::process::exit(1);
+                        }
+                    }
+                } else {
+                    eprintln!("Error: --sample requires a numeric argument");
+                    std::process::exit(1);
+                }
+            }
+            "--interval" | "-i" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<u32>() {
+                        Ok(n) if n >= 100 => {
+                            interval_ms = n;
+                            i += 1; // Skip the next argument since we consumed it
+                        }
+                        Ok(_) => {
+                            eprintln!("Error: Interval must be at least 100ms");
+                            std::process::exit(1);
+                        }
+                        Err(_) => {
+                            eprintln!("Error: Invalid interval '{}'", args[i + 1]);
+                            std::process::exit(1);
+                        }
+                    }
+                } else {
+                    eprintln!("Error: --interval requires a numeric argument");
+                    std::process::exit(1);
+                }
+            }
+            "--interfaces" => {
+                if i + 1 < args.len() {
+                    interface_filter = InterfaceFilter::parse(&args[i + 1]);
+                    i += 1; // Skip the next argument since we consumed it
+                } else {
+                    eprintln!("Error: --interfaces requires a comma-separated list");
+                    std::process::exit(1);
+                }
+            }
+            "--cgroup-root" => {
+                if i + 1 < args.len() {
+                    cgroup_root = args[i + 1].clone();
+                    i += 1; // Skip the next argument since we consumed it
+                } else {
+                    eprintln!("Error: --cgroup-root requires a path");
+                    std::process::exit(1);
+                }
+            }
+            "--fs-warn" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<f32>() {
+                        Ok(p) if (0.0..=100.0).contains(&p) => {
+                            fs_warn_percent = p;
+                            i += 1; // Skip the next argument since we consumed it
+                        }
+                        _ => {
+                            eprintln!("Error: Invalid percentage '{}'", args[i + 1]);
+                            std::process::exit(1);
+                        }
+                    }
+                } else {
+                    eprintln!("Error: --fs-warn requires a percentage");
+                    std::process::exit(1);
+                }
+            }
+            "--synthetic" => {
+                scenario.get_or_insert_with(Scenario::default);
+            }
+            "--scenario" => {
+                if i + 1 < args.len() {
+                    match Scenario::load(&args[i + 1]) {
+                        Ok(s) => scenario = Some(s),
+                        Err(e) => {
+                            eprintln!("Error: {}", e.reason());
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 1;
+                } else {
+                    eprintln!("Error: --scenario requires a file path");
+                    std::process::exit(1);
+                }
+            }
+            "--help" | "-h" => {
+                print_usage();
+                std::process::exit(0);
+            }
+            _ => {
+                eprintln!("Error: Unknown argument '{}'", args[i]);
+                eprintln!();
+                print_usage();
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    // Validate sample flag is only used with JSON
+    if sample_count.is_some() && !json_output {
+        eprintln!("Error: --sample can only be used with --json");
+        std::process::exit(1);
+    }
+
+    if print_summary && sample_count.is_none() {
+        eprintln!("Error: --summary can only be used with --sample");
+        std::process::exit(1);
+    }
+
+    if dashboard && (json_output || print_summary) {
+        eprintln!("Error: --dashboard can't be combined with --json or --summary");
+        std::process::exit(1);
+    }
+
+    let options = SamplerOptions {
+        interval: std::time::Duration::from_millis(interval_ms as u64),
+        interface_filter,
+        cgroup_root,
+        fs_warn_percent,
+        scenario,
+        ..Default::default()
+    };
+
+    // Synthetic data without --json goes to the dashboard
+    if dashboard || (!json_output && options.scenario.is_some()) {
+        run_dashboard(options);
+        return;
+    }
+
+    // Handle sampling mode for JSON output
+    if let Some(samples) = sample_count
+        && json_output
+    {
+        // Create sampler with cached resources
+        let mut sampler = match Sampler::with_options(options) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Error initializing sampler: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let mut counter = 0u32;
+
+        loop {
+            match sampler.sample() {
+                Ok(metrics) => {
+                    // Output JSON without pretty printing for streaming
+                    let json = serde_json::to_string(&metrics).unwrap();
+                    println!("{}", json);
+
+                    counter += 1;
+                    if samples > 0 && counter >= samples {
+                        break;
+                    }
+
+                    // No sleep - the interval is controlled by the sampling duration
+                }
+                Err(e) => {
+                    eprintln!("Error collecting metrics: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        if print_summary {
+            let summary = serde_json::json!({
+                "summary": {
+                    "samples": counter,
+                    "throttling": sampler.throttle_summary(),
+                }
+            });
+            println!("{}", summary);
+        }
+        return;
+    }
+
+    // Single collection mode
+    let result = Sampler::with_options(options).and_then(|mut sampler| sampler.sample());
+    let system_metrics = match result {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            eprintln!("Error collecting metrics: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if json_output {
+        // Output as JSON (not prettified for consistency with sampling mode)
+        let json = serde_json::to_string(&system_metrics).unwrap();
+        println!("{}", json);
+    } else {
+        // Output as human-readable text
+        println!("CPU Metrics:");
+        if let Some(ref chip) = system_metrics.cpu.chip_name {
+            println!("  Chip: {}", chip);
+        }
+        println!("  Brand: {}", system_metrics.cpu.cpu_brand);
+        println!("  Physical Cores: {}", system_metrics.cpu.physical_cores);
+        println!("  Logical Cores: {}", system_metrics.cpu.logical_cores);
+        if let Some(ecpu) = system_metrics.cpu.ecpu_cores {
+            println!("  Efficiency Cores: {}", ecpu);
+        }
+        if let Some(pcpu) = system_metrics.cpu.pcpu_cores {
+            println!("  Performance Cores: {}", pcpu);
+        }
+        if system_metrics.cpu.clusters.len() > 2 {
+            let layout: Vec<String> = system_metrics
+                .cpu
+                .clusters
+                .iter()
+                .map(|c| format!("{} ({} cores, die {})", c.id, c.cores, c.die))
+                .collect();
+            println!("  Clusters: {}", layout.join(", "));
+        }
+        println!("  Frequency: {} MHz", system_metrics.cpu.cpu_frequency_mhz);
+        if let Some(ref cpu_time) = system_metrics.cpu_time {
+            println!("  CPU Time: {}", format_cpu_times(&cpu_time.total));
+            for (i, core) in cpu_time.cores.iter().enumerate() {
+                println!("    cpu{}: {}", i, format_cpu_times(core));
+            }
+        }
+        if let Some(ref load) = system_metrics.load {
+            println!(
+                "  Load Average: {:.2} {:.2} {:.2}",
+                load.load_1, load.load_5, load.load_15
+            );
+            match (load.running_tasks, load.blocked_tasks) {
+                (Some(running), Some(blocked)) => {
+                    println!("  Tasks: {} running, {} blocked", running, blocked)
+                }
+                (Some(running), None) => println!("  Tasks: {} running", running),
+                _ => {}
+            }
+            let rate = |value: Option<f32>| {
+                value
+                    .map(|v| format!("{:.0}/s", v))
+                    .unwrap_or_else(|| "n/a".to_string())
+            };
+            println!(
+                "  Context Switches: {}, Interrupts: {}, Forks: {}",
+                rate(load.context_switches_per_sec),
+                rate(load.interrupts_per_sec),
+                rate(load.forks_per_sec)
+            );
+        }
+
+        // Performance metrics
+        if let Some((freq, util)) = system_metrics.ecpu_usage {
+            println!("  E-Core Usage: {} MHz ({:.1}%)", freq, util);
+        }
+        if let Some((freq, util)) = system_metrics.pcpu_usage {
+            println!("  P-Core Usage: {} MHz ({:.1}%)", freq, util);
+        }
+        // Per-cluster figures only add information when a kind has more than one cluster
+        if let Some(ref clusters) = system_metrics.clusters
+            && clusters.len() > 2
+        {
+            for cluster in clusters {
+                println!(
+                    "    {}: {} MHz ({:.1}%)",
+                    cluster.id, cluster.freq_mhz, cluster.utilization
+                );
+            }
+        }
+        if let Some((freq, util)) = system_metrics.gpu_usage {
+            println!("  GPU Usage: {} MHz ({:.1}%)", freq, util);
+        }
+        if let Some(ref throttling) = system_metrics.throttling {
+            for cluster in throttling {
+                println!(
+                    "  {} Cap: {}/{} MHz at {:.0}% active{}",
+                    cluster.cluster,
+                    cluster.freq_mhz,
+                    cluster.max_freq_mhz,
+                    cluster.active_ratio * 100.0,
+                    if cluster.throttled {
+                        " (THROTTLED)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+
+        println!("\nMemory Metrics:");
+        println!("  RAM:");
+        println!(
+            "    Total: {:.2} GB",
+            system_metrics.memory.ram_total as f64 / (1024.0 * 1024.0 * 1024.0)
+        );
+        println!(
+            "    Usage: {:.2} GB",
+            system_metrics.memory.ram_usage as f64 / (1024.0 * 1024.0 * 1024.0)
+        );
+        println!(
+            "    Used: {:.1}%",
+            (system_metrics.memory.ram_usage as f64 / system_metrics.memory.ram_total as f64)
+                * 100.0
+        );
+        println!("  Swap:");
+        println!(
+            "    Total: {:.2} GB",
+            system_metrics.memory.swap_total as f64 / (1024.0 * 1024.0 * 1024.0)
+        );
+        println!(
+            "    Usage: {:.2} GB",
+            system_metrics.memory.swap_usage as f64 / (1024.0 * 1024.0 * 1024.0)
+        );
+        if system_metrics.memory.swap_total > 0 {
+            println!(
+                "    Used: {:.1}%",
+                (system_metrics.memory.swap_usage as f64 / system_metrics.memory.swap_total as f64)
+                    * 100.0
+            );
+        } else {
+            println!("    Used: 0.0%");
+        }
+        let breakdown = &system_metrics.memory.breakdown;
+        println!("  Breakdown:");
+        for (label, bytes) in [
+            ("Active", breakdown.active),
+            ("Inactive", breakdown.inactive),
+            ("Wired", breakdown.wired),
+            ("Speculative", breakdown.speculative),
+            ("Compressed", breakdown.compressed),
+            ("Purgeable", breakdown.purgeable),
+            ("File-backed", breakdown.external),
+            ("Free", breakdown.free),
+        ] {
+            println!(
+                "    {}: {:.2} GB",
+                label,
+                bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            );
+        }
+        let activity = &system_metrics.memory.activity;
+        println!("  Activity (pages/s):");
+        println!(
+            "    Page ins/outs: {:.0}/{:.0}",
+            activity.pageins_per_sec, activity.pageouts_per_sec
+        );
+        println!(
+            "    Swap ins/outs: {:.0}/{:.0}",
+            activity.swapins_per_sec, activity.swapouts_per_sec
+        );
+        println!(
+            "    Compressions/decompressions: {:.0}/{:.0}",
+            activity.compressions_per_sec, activity.decompressions_per_sec
+        );
+        println!("  Pressure: {:?}", system_metrics.memory.pressure);
+
+        if let Some(ref pressure) = system_metrics.pressure {
+            println!("\nPressure Stalls (avg10/avg60/avg300):");
+            let resources = [
+                ("CPU", &pressure.cpu),
+                ("Memory", &pressure.memory),
+                ("I/O", &pressure.io),
+            ];
+            for (label, stalls) in resources {
+                let Some(stalls) = stalls else { continue };
+                let kinds = [("some", Some(&stalls.some)), ("full", stalls.full.as_ref())];
+                for (kind, stall) in kinds {
+                    if let Some(stall) = stall {
+                        println!(
+                            "  {:<6} {}: {:.2}% / {:.2}% / {:.2}% (total {:.1} s)",
+                            label,
+                            kind,
+                            stall.avg10,
+                            stall.avg60,
+                            stall.avg300,
+                            stall.total_us as f64 / 1e6
+                        );
+                    }
+                }
+            }
+            println!("  Memory pressure level: {:?}", pressure.memory_level);
+        }
+
+        if let Some(ref power) = system_metrics.power {
+            println!("\nPower Metrics:");
+            println!("  System Total: {:.2} W", power.sys_power);
+            println!("  CPU: {:.2} W", power.cpu_power);
+            println!("  GPU: {:.2} W", power.gpu_power);
+            if power.ane_power > 0.0 {
+                println!("  ANE (Neural Engine): {:.2} W", power.ane_power);
+            }
+            println!("  Memory: {:.2} W", power.ram_power);
+            if power.gpu_ram_power > 0.0 {
+                println!("  GPU Memory: {:.2} W", power.gpu_ram_power);
+            }
+            println!("  Combined (CPU+GPU+ANE): {:.2} W", power.all_power);
+        }
+
+        if let Some(ref disks) = system_metrics.disks
+            && !disks.is_empty()
+        {
+            println!("\nDisk Metrics:");
+            for disk in disks {
+                println!(
+                    "  {}: read {:.2} MB/s ({:.0} IOPS), write {:.2} MB/s ({:.0} IOPS), busy {:.1}%",
+                    disk.name,
+                    disk.read_bytes_per_sec as f64 / (1024.0 * 1024.0),
+                    disk.read_iops,
+                    disk.write_bytes_per_sec as f64 / (1024.0 * 1024.0),
+                    disk.write_iops,
+                    disk.busy_percent
+                );
+            }
+        }
+
+        if let Some(ref filesystems) = system_metrics.filesystems
+            && !filesystems.is_empty()
+        {
+            println!("\nFilesystems:");
+            for fs in filesystems {
+                println!(
+                    "  {} ({}, {}): {:.1}/{:.1} GB used ({:.1}%), {:.1} GB free, inodes {:.1}%{}",
+                    fs.mount_point,
+                    fs.device,
+                    fs.fs_type,
+                    fs.used_bytes as f64 / 1_073_741_824.0,
+                    fs.total_bytes as f64 / 1_073_741_824.0,
+                    fs.used_percent,
+                    fs.free_bytes as f64 / 1_073_741_824.0,
+                    fs.inodes_used_percent,
+                    if fs.warning { " (WARNING)" } else { "" }
+                );
+            }
+        }
+
+        if let Some(ref network) = system_metrics.network
+            && !network.is_empty()
+        {
+            println!("\nNetwork Metrics:");
+            for iface in network {
+                println!(
+                    "  {}: rx {:.2} MB/s ({:.0} pkt/s), tx {:.2} MB/s ({:.0} pkt/s), errors {:.0}/{:.0}, drops {:.0}/{:.0}",
+                    iface.name,
+                    iface.rx_bytes_per_sec as f64 / (1024.0 * 1024.0),
+                    iface.rx_packets_per_sec,
+                    iface.tx_bytes_per_sec as f64 / (1024.0 * 1024.0),
+                    iface.tx_packets_per_sec,
+                    iface.rx_errors_per_sec,
+                    iface.tx_errors_per_sec,
+                    iface.rx_drops_per_sec,
+                    iface.tx_drops_per_sec
+                );
+            }
+        }
+
+        if let Some(ref processes) = system_metrics.processes
+            && !processes.is_empty()
+        {
+            let mut top: Vec<&ProcessMetrics> = processes.iter().collect();
+            top.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+            println!("\nTop Processes (by CPU):");
+            for process in top.iter().take(10) {
+                println!(
+                    "  {:>7} {:<20} {:<10} CPU {:>5.1}%  RSS {:>8.1} MB  threads {:>3}  {:<8}{}{}",
+                    process.pid,
+                    process.name,
+                    process.user,
+                    process.cpu_percent,
+                    process.rss_bytes as f64 / (1024.0 * 1024.0),
+                    process.threads,
+                    process.state,
+                    process
+                        .energy_watts
+                        .map(|w| format!("  {:.2} W", w))
+                        .unwrap_or_default(),
+                    match (
+                        process.disk_read_bytes_per_sec,
+                        process.disk_write_bytes_per_sec
+                    ) {
+                        (Some(read), Some(write)) => format!(
+                            "  disk r/w {:.2}/{:.2} MB/s",
+                            read as f64 / (1024.0 * 1024.0),
+                            write as f64 / (1024.0 * 1024.0)
+                        ),
+                        _ => String::new(),
+                    }
+                );
+            }
+        }
+
+        if let Some(ref cgroups) = system_metrics.cgroups
+            && !cgroups.is_empty()
+        {
+            // Top two levels only; the JSON output has the full hierarchy
+            println!("\nCgroups:");
+            for group in cgroups.iter().filter(|g| g.path.matches('/').count() <= 2) {
+                println!(
+                    "  {:<40} CPU {:>5.1}%  Mem {:>8.1} MB  IO r/w {:.2}/{:.2} MB/s  procs {}{}",
+                    group.path,
+                    group.cpu_percent,
+                    group.memory_current as f64 / (1024.0 * 1024.0),
+                    group.io_read_bytes_per_sec as f64 / (1024.0 * 1024.0),
+                    group.io_write_bytes_per_sec as f64 / (1024.0 * 1024.0),
+                    group.processes,
+                    group
+                        .memory_pressure
+                        .as_ref()
+                        .map(|p| format!("  mem stall {:.1}%", p.some.avg10))
+                        .unwrap_or_default()
+                );
+            }
+        }
+
+        if let Some(ref battery) = system_metrics.battery {
+            println!("\nBattery Metrics:");
+            println!("  Charge: {:.0}%", battery.charge_percent);
+            println!(
+                "  State: {}",
+                if battery.charging {
+                    "Charging"
+                } else if battery.external_connected {
+                    "On AC power"
+                } else {
+                    "Discharging"
+                }
+            );
+            println!("  Power: {:.2} W", battery.power_watts);
+            println!("  Voltage: {:.2} V", battery.voltage_v);
+            println!("  Current: {:.2} A", battery.current_a);
+            if let Some(cycles) = battery.cycle_count {
+                println!("  Cycle Count: {}", cycles);
+            }
+            if let Some(minutes) = battery.time_to_empty_min {
+                println!("  Time to Empty: {}h {:02}m", minutes / 60, minutes % 60);
+            }
+            if let Some(minutes) = battery.time_to_full_min {
+                println!("  Time to Full: {}h {:02}m", minutes / 60, minutes % 60);
+            }
+            if let Some(watts) = battery.adapter_watts {
+                println!("  Adapter: {} W", watts);
+            }
+            if let Some(watts) = battery.system_power_watts {
+                println!("  System Power: {:.2} W", watts);
+            }
+        }
+
+        if !system_metrics.unavailable.is_empty() {
+            println!("\nUnavailable:");
+            for err in &system_metrics.unavailable {
+                println!("  {}", err);
+            }
+        }
+    }
+}
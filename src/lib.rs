@@ -0,0 +1,63 @@
+//! System metrics collection behind the `atop` binary, for tools that want the same data
+//! without spawning `atop --json`.
+//!
+//! ```
+//! use std::time::Duration;
+//! use atop::{Sampler, SamplerOptions, Subsystem};
+//!
+//! let mut sampler = Sampler::with_options(SamplerOptions {
+//!     interval: Duration::from_millis(100),
+//!     subsystems: vec![Subsystem::Power, Subsystem::Performance, Subsystem::Load],
+//!     ..Default::default()
+//! })?;
+//! for metrics in sampler.samples().take(2) {
+//!     let metrics = metrics?;
+//!     println!("{} MB used, power {:?}", metrics.memory.ram_usage / 1_048_576, metrics.power);
+//! }
+//! # Ok::<(), atop::AtopError>(())
+//! ```
+//!
+//! `BackgroundSampler` runs a `Sampler` on its own thread and pushes each sample to
+//! subscriptions (bounded channels that drop the oldest, drop the newest or block when full) and
+//! callbacks.
+//!
+//! ```
+//! use std::time::Duration;
+//! use atop::{BackgroundSampler, Backpressure, SamplerOptions, Subsystem};
+//!
+//! let background = BackgroundSampler::spawn(SamplerOptions {
+//!     interval: Duration::from_millis(100),
+//!     subsystems: vec![Subsystem::Power],
+//!     ..Default::default()
+//! })?;
+//! let power = background.subscribe(16, Backpressure::DropOldest);
+//! // ... run the workload ...
+//! let during = power.recv().unwrap()?;
+//! println!("{:?}", during.power.map(|p| p.cpu_power));
+//! background.stop();
+//! # Ok::<(), atop::AtopError>(())
+//! ```
+//!
+//! Subsystems that can't be read on this machine are `None` in `SystemMetrics` and listed with
+//! the reason in `SystemMetrics::unavailable`.
+
+mod cli;
+mod metrics;
+mod sampler;
+mod stream;
+mod ui;
+mod utils;
+
+pub use metrics::{
+    AtopError, BatteryMetrics, CgroupMetrics, ClusterSample, ClusterThrottle,
+    ClusterThrottleSummary, CpuCluster, CpuMetrics, CpuTimeMetrics, CpuTimes, DiskMetrics,
+    FilesystemMetrics, InterfaceFilter, LoadMetrics, MemoryActivity, MemoryBreakdown,
+    MemoryMetrics, MemoryPressure, NetworkMetrics, PowerMetrics, Pressure, PressureMetrics,
+    PressureStall, ProcessMetrics, Scenario, Subsystem, ThrottleSummary, Waveform,
+};
+pub use sampler::{Sampler, SamplerOptions, Samples, SystemMetrics};
+pub use stream::{BackgroundSampler, Backpressure, Subscription};
+
+// The binary is a thin wrapper so the collectors behind this API can stay private
+#[doc(hidden)]
+pub use cli::run;
@@ -0,0 +1,272 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use serde::Serialize;
+use std::error::Error;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct BatteryMetrics {
+    pub charge_percent: f32,
+    pub charging: bool,
+    pub external_connected: bool, // running on an adapter
+    pub power_watts: f32,         // positive while charging, negative while discharging
+    pub voltage_v: f32,           // Volts
+    pub current_a: f32,           // Amps, same sign convention as power_watts
+    pub cycle_count: Option<u32>,
+    pub time_to_empty_min: Option<u32>,
+    pub time_to_full_min: Option<u32>,
+    pub adapter_watts: Option<u32>,      // rated adapter wattage
+    pub system_power_watts: Option<f32>, // measured whole-system draw
+}
+
+/// Read battery and adapter state. Returns Ok(None) on machines without a battery.
+pub fn get_battery_metrics() -> Result<Option<BatteryMetrics>, AtopError> {
+    read_battery().subsystem(Subsystem::Battery)
+}
+
+#[cfg(target_os = "macos")]
+fn read_battery() -> Result<Option<BatteryMetrics>, Box<dyn Error>> {
+    use crate::metrics::iokit::{IOServiceIterator, get_io_props, io_object_release};
+    use crate::utils::iokit_utils::{cf_dict_get_bool, cf_dict_get_dict, cf_dict_get_i64};
+    use core_foundation::base::CFRelease;
+
+    // AppleSmartBattery reports 65535 when a time estimate is not available yet
+    const UNKNOWN_TIME: i64 = 65535;
+
+    let Some((entry, _)) = IOServiceIterator::new("AppleSmartBattery")?.next() else {
+        return Ok(None);
+    };
+    let props = get_io_props(entry);
+    io_object_release(entry);
+    let props = props?;
+
+    let current = cf_dict_get_i64(props, "CurrentCapacity").unwrap_or(0);
+    let max = cf_dict_get_i64(props, "MaxCapacity").unwrap_or(0);
+    let voltage_mv = cf_dict_get_i64(props, "Voltage").unwrap_or(0);
+    let amperage_ma = cf_dict_get_i64(props, "InstantAmperage")
+        .or_else(|| cf_dict_get_i64(props, "Amperage"))
+        .unwrap_or(0);
+
+    let voltage_v = voltage_mv as f32 / 1000.0;
+    let current_a = amperage_ma as f32 / 1000.0;
+    let time = |key: &str| {
+        cf_dict_get_i64(props, key)
+            .filter(|&t| t > 0 && t < UNKNOWN_TIME)
+            .map(|t| t as u32)
+    };
+
+    let mut metrics = BatteryMetrics {
+        charge_percent: if max > 0 {
+            current as f32 / max as f32 * 100.0
+        } else {
+            0.0
+        },
+        charging: cf_dict_get_bool(props, "IsCharging").unwrap_or(false),
+        external_connected: cf_dict_get_bool(props, "ExternalConnected").unwrap_or(false),
+        power_watts: voltage_v * current_a,
+        voltage_v,
+        current_a,
+        cycle_count: cf_dict_get_i64(props, "CycleCount").map(|c| c as u32),
+        time_to_empty_min: time("AvgTimeToEmpty"),
+        time_to_full_min: time("AvgTimeToFull"),
+        adapter_watts: cf_dict_get_dict(props, "AdapterDetails")
+            .and_then(|d| cf_dict_get_i64(d, "Watts"))
+            .filter(|&w| w > 0)
+            .map(|w| w as u32),
+        system_power_watts: None,
+    };
+
+    // Apple Silicon exposes the measured system load (mW) regardless of power source
+    metrics.system_power_watts = cf_dict_get_dict(props, "PowerTelemetryData")
+        .and_then(|d| cf_dict_get_i64(d, "SystemLoad"))
+        .filter(|&mw| mw > 0)
+        .map(|mw| mw as f32 / 1000.0)
+        .or_else(|| discharge_power(&metrics));
+
+    unsafe { CFRelease(props as _) };
+
+    Ok(Some(metrics))
+}
+
+#[cfg(target_os = "linux")]
+fn read_battery() -> Result<Option<BatteryMetrics>, Box<dyn Error>> {
+    read_power_supply(std::path::Path::new("/sys/class/power_supply"))
+}
+
+#[cfg(target_os = "linux")]
+fn read_power_supply(root: &std::path::Path) -> Result<Option<BatteryMetrics>, Box<dyn Error>> {
+    use crate::utils::procfs::{read_i64, read_trimmed, read_u64};
+
+    let mut battery = None;
+    let mut external_connected = false;
+    let mut adapter_watts = None;
+
+    let mut entries: Vec<_> = std::fs::read_dir(root)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+
+    for dir in entries {
+        let supply_type = read_trimmed(dir.join("type")).unwrap_or_default();
+        match supply_type.as_str() {
+            "Mains" | "USB" if read_u64(dir.join("online")) == Some(1) => {
+                external_connected = true;
+                // Only USB-PD style supplies advertise their limits
+                if let (Some(uv), Some(ua)) = (
+                    read_u64(dir.join("voltage_max")),
+                    read_u64(dir.join("current_max")),
+                ) {
+                    let watts = (uv as f64 * ua as f64 / 1e12) as u32;
+                    if watts > 0 {
+                        adapter_watts = Some(watts);
+                    }
+                }
+            }
+            // Skip peripheral batteries (mice, keyboards) which report scope "Device"
+            "Battery" if battery.is_none() && read_trimmed(dir.join("scope")).is_none() => {
+                battery = Some(dir);
+            }
+            _ => {}
+        }
+    }
+
+    let Some(dir) = battery else {
+        return Ok(None);
+    };
+
+    let status = read_trimmed(dir.join("status")).unwrap_or_default();
+    let charging = status == "Charging";
+    let discharging = status == "Discharging";
+
+    // sysfs values are in micro-units; current/power may be reported unsigned
+    let voltage_v = read_u64(dir.join("voltage_now")).unwrap_or(0) as f32 / 1e6;
+    let current = read_i64(dir.join("current_now")).map(|ua| ua.unsigned_abs() as f32 / 1e6);
+    let power = read_i64(dir.join("power_now")).map(|uw| uw.unsigned_abs() as f32 / 1e6);
+    let (current_a, power_w) = match (current, power) {
+        (Some(a), Some(w)) => (a, w),
+        (Some(a), None) => (a, voltage_v * a),
+        (None, Some(w)) if voltage_v > 0.0 => (w / voltage_v, w),
+        _ => (0.0, power.unwrap_or(0.0)),
+    };
+    let sign = if discharging { -1.0 } else { 1.0 };
+
+    // Prefer energy (µWh) counters, fall back to charge (µAh) counters
+    let (now, full, rate) = match (
+        read_u64(dir.join("energy_now")),
+        read_u64(dir.join("energy_full")),
+    ) {
+        (Some(now), Some(full)) => (now as f32, full as f32, power_w * 1e6),
+        _ => (
+            read_u64(dir.join("charge_now")).unwrap_or(0) as f32,
+            read_u64(dir.join("charge_full")).unwrap_or(0) as f32,
+            current_a * 1e6,
+        ),
+    };
+    let minutes = |amount: f32| {
+        if rate > 0.0 {
+            Some((amount / rate * 60.0) as u32)
+        } else {
+            None
+        }
+    };
+
+    let charge_percent = read_u64(dir.join("capacity"))
+        .map(|c| c as f32)
+        .unwrap_or(if full > 0.0 { now / full * 100.0 } else { 0.0 });
+
+    let mut metrics = BatteryMetrics {
+        charge_percent,
+        charging,
+        external_connected: external_connected || !discharging,
+        power_watts: power_w * sign,
+        voltage_v,
+        current_a: current_a * sign,
+        cycle_count: read_u64(dir.join("cycle_count"))
+            .filter(|&c| c > 0)
+            .map(|c| c as u32),
+        time_to_empty_min: if discharging { minutes(now) } else { None },
+        time_to_full_min: if charging { minutes(full - now) } else { None },
+        adapter_watts,
+        system_power_watts: None,
+    };
+    metrics.system_power_watts = discharge_power(&metrics);
+
+    Ok(Some(metrics))
+}
+
+// While running on battery alone, its discharge rate is the whole-system draw
+fn discharge_power(metrics: &BatteryMetrics) -> Option<f32> {
+    if !metrics.external_connected && metrics.power_watts < 0.0 {
+        Some(-metrics.power_watts)
+    } else {
+        None
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::utils::procfs::fixture_dir;
+
+    #[test]
+    fn reads_a_discharging_battery_and_skips_peripherals() {
+        let root = fixture_dir(
+            "power-supply-discharging",
+            &[
+                ("AC/type", "Mains\n"),
+                ("AC/online", "0\n"),
+                // A wireless mouse sorts first but must not be taken for the system battery
+                ("hidpp_battery_0/type", "Battery\n"),
+                ("hidpp_battery_0/scope", "Device\n"),
+                ("hidpp_battery_0/capacity", "90\n"),
+                ("macsmc-battery/type", "Battery\n"),
+                ("macsmc-battery/status", "Discharging\n"),
+                ("macsmc-battery/capacity", "50\n"),
+                ("macsmc-battery/voltage_now", "12000000\n"),
+                ("macsmc-battery/power_now", "6000000\n"),
+                ("macsmc-battery/energy_now", "30000000\n"),
+                ("macsmc-battery/energy_full", "60000000\n"),
+                ("macsmc-battery/cycle_count", "120\n"),
+            ],
+        );
+        let battery = read_power_supply(&root).unwrap().unwrap();
+        assert_eq!(battery.charge_percent, 50.0);
+        assert!(!battery.charging && !battery.external_connected);
+        assert_eq!(battery.power_watts, -6.0);
+        assert_eq!(battery.current_a, -0.5);
+        assert_eq!(battery.voltage_v, 12.0);
+        assert_eq!(battery.cycle_count, Some(120));
+        assert_eq!(battery.time_to_empty_min, Some(300));
+        assert_eq!(battery.time_to_full_min, None);
+        assert_eq!(battery.adapter_watts, None);
+        assert_eq!(battery.system_power_watts, Some(6.0));
+    }
+
+    #[test]
+    fn reads_a_charging_battery_from_charge_counters() {
+        let root = fixture_dir(
+            "power-supply-charging",
+            &[
+                ("BAT0/type", "Battery\n"),
+                ("BAT0/status", "Charging\n"),
+                ("BAT0/voltage_now", "11500000\n"),
+                ("BAT0/current_now", "2000000\n"),
+                ("BAT0/charge_now", "2000000\n"),
+                ("BAT0/charge_full", "5000000\n"),
+                ("ucsi-source-psy-USBC000:001/type", "USB\n"),
+                ("ucsi-source-psy-USBC000:001/online", "1\n"),
+                ("ucsi-source-psy-USBC000:001/voltage_max", "20000000\n"),
+                ("ucsi-source-psy-USBC000:001/current_max", "3250000\n"),
+            ],
+        );
+        let battery = read_power_supply(&root).unwrap().unwrap();
+        // No capacity file: the percentage comes from the charge counters
+        assert_eq!(battery.charge_percent, 40.0);
+        assert!(battery.charging && battery.external_connected);
+        assert_eq!(battery.power_watts, 23.0);
+        assert_eq!(battery.time_to_full_min, Some(90));
+        assert_eq!(battery.adapter_watts, Some(65));
+        assert_eq!(battery.system_power_watts, None);
+
+        let empty = fixture_dir("power-supply-none", &[("AC/type", "Mains\n")]);
+        assert!(read_power_supply(&empty).unwrap().is_none());
+    }
+}
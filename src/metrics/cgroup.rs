@@ -0,0 +1,286 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use crate::metrics::pressure::{Pressure, parse_pressure};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+pub const DEFAULT_CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct CgroupMetrics {
+    pub path: String,        // relative to the cgroupfs root, "/" for the root group
+    pub processes: u32,      // direct members (cgroup.procs)
+    pub cpu_percent: f32,    // includes descendants; 100% = one core fully busy
+    pub memory_current: u64, // bytes, includes descendants
+    pub memory_anon: u64,    // bytes (memory.stat)
+    pub memory_file: u64,    // bytes (memory.stat)
+    pub memory_kernel: u64,  // bytes (memory.stat)
+    pub io_read_bytes_per_sec: u64,
+    pub io_write_bytes_per_sec: u64,
+    pub cpu_pressure: Option<Pressure>,
+    pub memory_pressure: Option<Pressure>,
+    pub io_pressure: Option<Pressure>,
+}
+
+// Cumulative per-group counters
+#[derive(Debug, Default, Clone)]
+struct CgroupCounters {
+    cpu_usage_us: u64,
+    io_read_bytes: u64,
+    io_write_bytes: u64,
+}
+
+/// Walks a cgroup v2 hierarchy and computes per-group rates between samples
+pub struct CgroupSampler {
+    root: PathBuf,
+    prev: HashMap<String, CgroupCounters>,
+    prev_time: Instant,
+}
+
+impl CgroupSampler {
+    /// `root` is the cgroup2 mount point, normally `/sys/fs/cgroup`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let prev = read_cgroups(&root)
+            .map(|groups| {
+                groups
+                    .into_iter()
+                    .map(|(metrics, counters)| (metrics.path, counters))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            root,
+            prev,
+            prev_time: Instant::now(),
+        }
+    }
+
+    /// Groups sorted by path (parents before children), with rates since the previous call
+    pub fn sample(&mut self) -> Result<Vec<CgroupMetrics>, AtopError> {
+        let groups = read_cgroups(&self.root).subsystem(Subsystem::Cgroup)?;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.prev_time).as_secs_f64();
+
+        let mut prev = HashMap::with_capacity(groups.len());
+        let mut cgroups = Vec::with_capacity(groups.len());
+        for (mut metrics, cur) in groups {
+            let old = self.prev.get(&metrics.path).cloned().unwrap_or(cur.clone());
+            if elapsed > 0.0 {
+                let rate = |cur: u64, prev: u64| cur.saturating_sub(prev) as f64 / elapsed;
+                // usage_usec per second of wall time, as a percentage of one core
+                metrics.cpu_percent = (rate(cur.cpu_usage_us, old.cpu_usage_us) / 1e4) as f32;
+                metrics.io_read_bytes_per_sec = rate(cur.io_read_bytes, old.io_read_bytes) as u64;
+                metrics.io_write_bytes_per_sec =
+                    rate(cur.io_write_bytes, old.io_write_bytes) as u64;
+            }
+            prev.insert(metrics.path.clone(), cur);
+            cgroups.push(metrics);
+        }
+        cgroups.sort_by(|a, b| a.path.cmp(&b.path));
+
+        self.prev = prev;
+        self.prev_time = now;
+
+        Ok(cgroups)
+    }
+}
+
+impl Default for CgroupSampler {
+    fn default() -> Self {
+        Self::new(DEFAULT_CGROUP_ROOT)
+    }
+}
+
+fn read_cgroups(root: &Path) -> Result<Vec<(CgroupMetrics, CgroupCounters)>, Box<dyn Error>> {
+    // cgroup.controllers only exists on a cgroup2 hierarchy
+    if !root.join("cgroup.controllers").exists() {
+        let reason = format!("{} is not a cgroup v2 hierarchy", root.display());
+        return Err(AtopError::unavailable(Subsystem::Cgroup, reason).into());
+    }
+
+    let mut groups = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        groups.push(read_cgroup(root, &dir));
+
+        // Child groups are the subdirectories
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            pending.extend(
+                entries
+                    .flatten()
+                    .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                    .map(|e| e.path()),
+            );
+        }
+    }
+
+    Ok(groups)
+}
+
+fn read_cgroup(root: &Path, dir: &Path) -> (CgroupMetrics, CgroupCounters) {
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+    let path = match dir.strip_prefix(root) {
+        Ok(rel) if !rel.as_os_str().is_empty() => format!("/{}", rel.display()),
+        _ => "/".to_string(),
+    };
+
+    let cpu_stat = read("cpu.stat").map(|c| flat_keyed(&c)).unwrap_or_default();
+    let memory_stat = read("memory.stat")
+        .map(|c| flat_keyed(&c))
+        .unwrap_or_default();
+    let memory = |key: &str| memory_stat.get(key).copied().unwrap_or(0);
+
+    // io.stat has one line per device: "8:0 rbytes=.. wbytes=.. rios=.. wios=.."
+    let (mut io_read, mut io_write) = (0, 0);
+    for line in read("io.stat").unwrap_or_default().lines() {
+        for field in line.split_whitespace().skip(1) {
+            match field.split_once('=') {
+                Some(("rbytes", v)) => io_read += v.parse::<u64>().unwrap_or(0),
+                Some(("wbytes", v)) => io_write += v.parse::<u64>().unwrap_or(0),
+                _ => {}
+            }
+        }
+    }
+
+    let metrics = CgroupMetrics {
+        path,
+        processes: read("cgroup.procs")
+            .map(|c| c.lines().filter(|l| !l.trim().is_empty()).count() as u32)
+            .unwrap_or(0),
+        cpu_percent: 0.0,
+        memory_current: read("memory.current")
+            .and_then(|c| c.trim().parse().ok())
+            .unwrap_or(0),
+        memory_anon: memory("anon"),
+        memory_file: memory("file"),
+        // Older kernels have no aggregate "kernel" entry
+        memory_kernel: memory_stat
+            .get("kernel")
+            .copied()
+            .unwrap_or_else(|| memory("kernel_stack") + memory("slab") + memory("pagetables")),
+        io_read_bytes_per_sec: 0,
+        io_write_bytes_per_sec: 0,
+        cpu_pressure: read("cpu.pressure").and_then(|c| parse_pressure(&c)),
+        memory_pressure: read("memory.pressure").and_then(|c| parse_pressure(&c)),
+        io_pressure: read("io.pressure").and_then(|c| parse_pressure(&c)),
+    };
+    let counters = CgroupCounters {
+        cpu_usage_us: cpu_stat.get("usage_usec").copied().unwrap_or(0),
+        io_read_bytes: io_read,
+        io_write_bytes: io_write,
+    };
+
+    (metrics, counters)
+}
+
+// "key value" per line, as in cpu.stat and memory.stat
+fn flat_keyed(content: &str) -> HashMap<String, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::utils::procfs::fixture_dir;
+    use std::time::Duration;
+
+    const PRESSURE: &str = "\
+some avg10=12.50 avg60=4.00 avg300=1.00 total=900000
+full avg10=2.50 avg60=1.00 avg300=0.25 total=100000
+";
+
+    fn write(root: &Path, file: &str, contents: &str) {
+        std::fs::write(root.join(file), contents).unwrap();
+    }
+
+    #[test]
+    fn reads_a_cgroup_tree_and_computes_rates() {
+        let root = fixture_dir(
+            "cgroup",
+            &[
+                ("cgroup.controllers", "cpu io memory pids\n"),
+                ("cgroup.procs", "1\n2\n"),
+                ("cpu.stat", "usage_usec 5000000\nuser_usec 4000000\n"),
+                ("memory.pressure", PRESSURE),
+                ("db.slice/cgroup.procs", "100\n101\n102\n"),
+                (
+                    "db.slice/cpu.stat",
+                    "usage_usec 1000000\nuser_usec 800000\n",
+                ),
+                ("db.slice/memory.current", "734003200\n"),
+                (
+                    "db.slice/memory.stat",
+                    "anon 500000000\nfile 200000000\nkernel_stack 1000000\nslab 3000000\npagetables 2000000\n",
+                ),
+                (
+                    "db.slice/io.stat",
+                    "8:0 rbytes=1000 wbytes=2000 rios=1 wios=2\n8:16 rbytes=3000 wbytes=4000 rios=3 wios=4\n",
+                ),
+                ("db.slice/cpu.pressure", PRESSURE),
+                ("db.slice/memory.pressure", PRESSURE),
+                ("db.slice/web/cgroup.procs", ""),
+            ],
+        );
+
+        let mut sampler = CgroupSampler::new(&root);
+        // One more core-second of CPU and 4 MB read / 8 MB written across both devices
+        write(&root, "db.slice/cpu.stat", "usage_usec 2000000\n");
+        write(
+            &root,
+            "db.slice/io.stat",
+            "8:0 rbytes=2001000 wbytes=4002000\n8:16 rbytes=2003000 wbytes=4004000\n",
+        );
+        sampler.prev_time = Instant::now() - Duration::from_secs(2);
+        let groups = sampler.sample().unwrap();
+
+        let paths: Vec<&str> = groups.iter().map(|g| g.path.as_str()).collect();
+        assert_eq!(paths, ["/", "/db.slice", "/db.slice/web"]);
+
+        let top = &groups[0];
+        assert_eq!(top.processes, 2);
+        assert_eq!(top.cpu_percent, 0.0);
+        assert!(top.cpu_pressure.is_none());
+        assert_eq!(top.memory_pressure.as_ref().unwrap().some.avg10, 12.5);
+
+        let db = &groups[1];
+        assert_eq!(db.processes, 3);
+        assert_eq!(db.memory_current, 734003200);
+        assert_eq!((db.memory_anon, db.memory_file), (500000000, 200000000));
+        // No aggregate "kernel" entry, so the parts are summed
+        assert_eq!(db.memory_kernel, 6000000);
+        let cpu = db.cpu_pressure.as_ref().unwrap();
+        assert_eq!((cpu.some.avg10, cpu.some.total_us), (12.5, 900000));
+        assert_eq!(cpu.full.as_ref().unwrap().avg10, 2.5);
+        assert!(db.io_pressure.is_none());
+
+        // Rates over the ~2 s since the baseline
+        assert!((db.cpu_percent - 50.0).abs() < 0.5, "{}", db.cpu_percent);
+        assert!(db.io_read_bytes_per_sec.abs_diff(2_000_000) < 20_000);
+        assert!(db.io_write_bytes_per_sec.abs_diff(4_000_000) < 40_000);
+
+        let web = &groups[2];
+        assert_eq!(
+            (web.processes, web.memory_current, web.cpu_percent),
+            (0, 0, 0.0)
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn a_cgroup_v1_root_is_unavailable() {
+        let root = fixture_dir("cgroup-v1", &[("cpu/cpu.shares", "1024\n")]);
+        let err = CgroupSampler::new(&root).sample().unwrap_err();
+        assert!(matches!(err, AtopError::Unavailable { .. }), "{:?}", err);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+// Per-chip tables of IORegistry keys, IOReport channel names and cluster layouts.
+// Built-in profiles can be overridden from a JSON file so new chips work without a release.
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::metrics::cpu::CpuCluster;
+use crate::metrics::error::{AtopError, Subsystem};
+use crate::metrics::ioreport_sample::FreqTables;
+
+/// How to read DVFS tables and IOReport channels on one chip family
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChipProfile {
+    pub name: String, // matched against the CPU brand, e.g. "M1 Pro"; the longest match wins
+    // pmgr properties holding (frequency, voltage) pairs
+    pub ecpu_dvfs_key: String,
+    pub pcpu_dvfs_key: String,
+    pub gpu_dvfs_key: String,
+    // One key per cluster where clusters of a kind have separate tables; empty means shared
+    pub ecpu_cluster_dvfs_keys: Vec<String>,
+    pub pcpu_cluster_dvfs_keys: Vec<String>,
+    // "Energy Model" channel names, `*` matches any text
+    pub cpu_energy: Vec<String>,
+    pub ecpu_energy: Vec<String>, // E-cluster share of cpu_energy
+    pub pcpu_energy: Vec<String>, // P-cluster share of cpu_energy
+    pub gpu_energy: Vec<String>,
+    pub ane_energy: Vec<String>,
+    pub dram_energy: Vec<String>,
+    pub gpu_sram_energy: Vec<String>,
+    // Performance state channel names
+    pub ecpu_states: Vec<String>,
+    pub pcpu_states: Vec<String>,
+    pub gpu_states: Vec<String>,
+    // Cluster layout; clusters of each kind are spread evenly over the dies
+    pub ecpu_clusters: u32,
+    pub pcpu_clusters: u32,
+    pub dies: u32,
+}
+
+impl ChipProfile {
+    /// The profile's clusters with the cores of each kind split evenly over them,
+    /// in core order (the earlier clusters take the remainder)
+    pub fn clusters(
+        &self,
+        ecpu_cores: u32,
+        pcpu_cores: u32,
+        tables: &FreqTables,
+    ) -> Vec<CpuCluster> {
+        let kinds = [
+            (
+                true,
+                "E",
+                self.ecpu_clusters,
+                ecpu_cores,
+                &tables.ecpu_mhz,
+                &tables.ecpu_cluster_mhz,
+            ),
+            (
+                false,
+                "P",
+                self.pcpu_clusters,
+                pcpu_cores,
+                &tables.pcpu_mhz,
+                &tables.pcpu_cluster_mhz,
+            ),
+        ];
+        let dies = self.dies.max(1);
+        let mut clusters = Vec::new();
+        for (efficiency, prefix, count, cores, shared, per_cluster) in kinds {
+            let count = count.max(1);
+            for i in 0..count {
+                let freqs_mhz = match per_cluster.get(i as usize) {
+                    Some(freqs) if !freqs.is_empty() => freqs.clone(),
+                    _ => shared.clone(),
+                };
+                clusters.push(CpuCluster {
+                    id: format!("{}{}", prefix, i),
+                    efficiency,
+                    die: i * dies / count,
+                    cores: cores / count + u32::from(i < cores % count),
+                    freqs_mhz,
+                });
+            }
+        }
+        clusters
+    }
+}
+
+impl Default for ChipProfile {
+    // Used for chips without a profile; matches every M1 - M4 variant seen so far
+    fn default() -> Self {
+        let patterns = |list: &[&str]| list.iter().map(|p| p.to_string()).collect();
+        Self {
+            name: String::new(),
+            ecpu_dvfs_key: "voltage-states1-sram".to_string(),
+            pcpu_dvfs_key: "voltage-states5-sram".to_string(),
+            gpu_dvfs_key: "voltage-states9".to_string(),
+            ecpu_cluster_dvfs_keys: Vec::new(),
+            pcpu_cluster_dvfs_keys: Vec::new(),
+            cpu_energy: patterns(&["*CPU Energy"]),
+            // ECPU/PCPU (M1) or EACC_CPU/PACC0_CPU (Pro/Max)
+            ecpu_energy: patterns(&["E*CPU Energy"]),
+            pcpu_energy: patterns(&["P*CPU Energy"]),
+            gpu_energy: patterns(&["GPU Energy"]),
+            ane_energy: patterns(&["ANE*"]),
+            dram_energy: patterns(&["DRAM*"]),
+            gpu_sram_energy: patterns(&["GPU SRAM*"]),
+            ecpu_states: patterns(&["*ECPU*"]),
+            pcpu_states: patterns(&["*PCPU*"]),
+            gpu_states: patterns(&["GPUPH"]),
+            ecpu_clusters: 1,
+            pcpu_clusters: 1,
+            dies: 1,
+        }
+    }
+}
+
+// (name, E-clusters, P-clusters, dies); all of them share the default keys and channel names
+const BUILTIN_LAYOUTS: &[(&str, u32, u32, u32)] = &[
+    ("M1", 1, 1, 1),
+    ("M1 Pro", 1, 2, 1),
+    ("M1 Max", 1, 2, 1),
+    ("M1 Ultra", 2, 4, 2),
+    ("M2", 1, 1, 1),
+    ("M2 Pro", 1, 2, 1),
+    ("M2 Max", 1, 2, 1),
+    ("M2 Ultra", 2, 4, 2),
+    ("M3", 1, 1, 1),
+    ("M3 Pro", 1, 1, 1),
+    ("M3 Max", 1, 2, 1),
+    ("M3 Ultra", 2, 4, 2),
+    ("M4", 1, 1, 1),
+    ("M4 Pro", 1, 2, 1),
+    ("M4 Max", 1, 2, 1),
+];
+
+/// Channel name patterns: `*` matches any run of characters
+pub fn matches_any(patterns: &[String], name: &str) -> bool {
+    patterns.iter().any(|p| glob_match(p, name))
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = text.strip_prefix(parts.next().unwrap_or("")) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    // No `*` at all: exact match
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// The set of known chip profiles
+#[derive(Debug, Clone)]
+pub struct ChipProfiles {
+    profiles: Vec<ChipProfile>,
+}
+
+impl ChipProfiles {
+    pub fn builtin() -> Self {
+        Self {
+            profiles: BUILTIN_LAYOUTS
+                .iter()
+                .map(|&(name, ecpu_clusters, pcpu_clusters, dies)| ChipProfile {
+                    name: name.to_string(),
+                    ecpu_clusters,
+                    pcpu_clusters,
+                    dies,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    /// Built-in profiles with the override file applied, if there is one
+    pub fn load() -> Result<Self, AtopError> {
+        let mut profiles = Self::builtin();
+        if let Some(path) = override_path()
+            && path.exists()
+        {
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                AtopError::unavailable(
+                    Subsystem::Cpu,
+                    format!("Failed to read {}: {}", path.display(), e),
+                )
+            })?;
+            profiles.apply_overrides(&content).map_err(|e| {
+                AtopError::read(
+                    Subsystem::Cpu,
+                    format!("Invalid chip profiles {}: {}", path.display(), e.reason()),
+                )
+            })?;
+        }
+        Ok(profiles)
+    }
+
+    /// Merge a JSON array of (partial) profiles: fields left out keep the values of the
+    /// profile with the same name, or the defaults for a new chip
+    pub fn apply_overrides(&mut self, json: &str) -> Result<(), AtopError> {
+        self.merge(json)
+            .map_err(|e| AtopError::read(Subsystem::Cpu, e))
+    }
+
+    fn merge(&mut self, json: &str) -> Result<(), Box<dyn Error>> {
+        let overrides: Vec<serde_json::Map<String, serde_json::Value>> =
+            serde_json::from_str(json)?;
+        for fields in overrides {
+            let name = fields
+                .get("name")
+                .and_then(|n| n.as_str())
+                .ok_or("every profile needs a name")?
+                .to_string();
+            let index = self.profiles.iter().position(|p| p.name == name);
+            let base = index.map(|i| self.profiles[i].clone()).unwrap_or_default();
+
+            let mut merged = serde_json::to_value(base)?;
+            if let Some(object) = merged.as_object_mut() {
+                object.extend(fields);
+            }
+            let profile: ChipProfile = serde_json::from_value(merged)?;
+            match index {
+                Some(i) => self.profiles[i] = profile,
+                None => self.profiles.push(profile),
+            }
+        }
+        Ok(())
+    }
+
+    /// Profile whose name appears as whole words in `chip` ("Apple M1 Pro" -> "M1 Pro")
+    pub fn select(&self, chip: &str) -> ChipProfile {
+        let chip = format!(" {} ", chip);
+        self.profiles
+            .iter()
+            .filter(|p| !p.name.is_empty() && chip.contains(&format!(" {} ", p.name)))
+            .max_by_key(|p| p.name.len())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// `$ATOP_CHIP_PROFILES`, else `chips.json` in the user's atop config directory
+pub fn override_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("ATOP_CHIP_PROFILES") {
+        return Some(PathBuf::from(path));
+    }
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("atop").join("chips.json"))
+}
+
+/// Profile of the running machine, resolved once; a broken override file falls back to the built-ins
+#[cfg(target_os = "macos")]
+pub fn active_profile() -> &'static ChipProfile {
+    static PROFILE: std::sync::OnceLock<ChipProfile> = std::sync::OnceLock::new();
+    PROFILE.get_or_init(|| {
+        // Apple Silicon reports e.g. "Apple M2 Max" here
+        let brand = crate::utils::iokit_utils::sysctl_string("machdep.cpu.brand_string")
+            .unwrap_or_default();
+        ChipProfiles::load()
+            .unwrap_or_else(|_| ChipProfiles::builtin())
+            .select(&brand)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_the_longest_matching_profile() {
+        let profiles = ChipProfiles::builtin();
+        assert_eq!(profiles.select("Apple M1").name, "M1");
+        assert_eq!(profiles.select("Apple M1 Pro").name, "M1 Pro");
+        assert_eq!(profiles.select("Apple M2 Ultra").dies, 2);
+        // Unknown chips get the generic profile
+        assert_eq!(profiles.select("Apple M10").name, "");
+        assert_eq!(
+            profiles.select("Intel(R) Core(TM) i9"),
+            ChipProfile::default()
+        );
+    }
+
+    #[test]
+    fn merges_partial_overrides() {
+        let mut profiles = ChipProfiles::builtin();
+        profiles
+            .apply_overrides(
+                r#"[
+                    {"name": "M1 Max", "pcpu_dvfs_key": "voltage-states6-sram"},
+                    {"name": "M5", "pcpu_clusters": 3, "gpu_energy": ["GPU0 Energy", "GPU1*"]}
+                ]"#,
+            )
+            .unwrap();
+
+        let max = profiles.select("Apple M1 Max");
+        assert_eq!(max.pcpu_dvfs_key, "voltage-states6-sram");
+        assert_eq!(max.pcpu_clusters, 2); // kept from the built-in profile
+        let m5 = profiles.select("Apple M5");
+        assert_eq!(m5.pcpu_clusters, 3);
+        assert_eq!(m5.ecpu_dvfs_key, "voltage-states1-sram");
+        assert!(matches_any(&m5.gpu_energy, "GPU1 Energy"));
+
+        assert!(
+            profiles
+                .apply_overrides(r#"[{"pcpu_clusters": 2}]"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn splits_cores_over_clusters_and_dies() {
+        let tables = FreqTables {
+            ecpu_mhz: vec![600, 2064],
+            pcpu_mhz: vec![600, 3228],
+            pcpu_cluster_mhz: vec![vec![], vec![600, 3000]],
+            ..Default::default()
+        };
+        let clusters = ChipProfiles::builtin()
+            .select("Apple M1 Ultra")
+            .clusters(4, 14, &tables);
+
+        let summary: Vec<(&str, u32, u32)> = clusters
+            .iter()
+            .map(|c| (c.id.as_str(), c.die, c.cores))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("E0", 0, 2),
+                ("E1", 1, 2),
+                ("P0", 0, 4),
+                ("P1", 0, 4),
+                ("P2", 1, 3),
+                ("P3", 1, 3)
+            ]
+        );
+        assert!(clusters[0].efficiency && !clusters[2].efficiency);
+        assert_eq!(clusters[2].freqs_mhz, [600, 3228]);
+        assert_eq!(clusters[3].freqs_mhz, [600, 3000]);
+    }
+
+    #[test]
+    fn matches_channel_patterns() {
+        let profile = ChipProfile::default();
+        assert!(matches_any(&profile.ecpu_energy, "EACC_CPU Energy"));
+        assert!(matches_any(&profile.ecpu_energy, "ECPU Energy"));
+        assert!(!matches_any(&profile.ecpu_energy, "PACC0_CPU Energy"));
+        assert!(matches_any(&profile.cpu_energy, "CPU Energy"));
+        assert!(matches_any(&profile.pcpu_states, "PCPU12"));
+        assert!(matches_any(&profile.gpu_states, "GPUPH"));
+        assert!(!matches_any(&profile.gpu_states, "GPUPH2"));
+    }
+}
@@ -1,7 +1,12 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+#[cfg(target_os = "macos")]
 use crate::metrics::iokit;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "macos")]
 use std::ffi::CString;
+#[cfg(target_os = "macos")]
 use std::mem;
+#[cfg(target_os = "macos")]
 use std::process::Command;
 
 #[derive(Debug, Default, Clone, Serialize)]
@@ -15,14 +20,27 @@ pub struct CpuMetrics {
     pub pcpu_cores: Option<u32>,
     pub ecpu_freqs_mhz: Option<Vec<u32>>,
     pub pcpu_freqs_mhz: Option<Vec<u32>>,
+    pub clusters: Vec<CpuCluster>, // Apple Silicon only, E-clusters first
 }
 
+/// One CPU cluster, with its own DVFS table
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CpuCluster {
+    pub id: String, // "E0", "P0", "P1", ... numbered per kind across dies
+    pub efficiency: bool,
+    pub die: u32,
+    pub cores: u32,
+    pub freqs_mhz: Vec<u32>,
+}
+
+#[cfg(target_os = "macos")]
 #[derive(Debug, Default)]
 pub struct CpuInfo {
     pub ecpu_freqs_mhz: Vec<u32>,
     pub pcpu_freqs_mhz: Vec<u32>,
 }
 
+#[cfg(target_os = "macos")]
 pub fn get_cpu_info() -> Result<CpuInfo, Box<dyn std::error::Error>> {
     let (ecpu_freqs_mhz, pcpu_freqs_mhz, _) = iokit::get_cpu_frequencies()?;
     Ok(CpuInfo {
@@ -31,12 +49,18 @@ pub fn get_cpu_info() -> Result<CpuInfo, Box<dyn std::error::Error>> {
     })
 }
 
+#[cfg(target_os = "macos")]
 pub fn get_gpu_freqs() -> Result<Vec<u32>, Box<dyn std::error::Error>> {
     let (_, gpu_freqs, _) = iokit::get_gpu_frequencies()?;
     Ok(gpu_freqs.unwrap_or_default())
 }
 
-pub fn get_cpu_metrics() -> Result<CpuMetrics, Box<dyn std::error::Error>> {
+pub fn get_cpu_metrics() -> Result<CpuMetrics, AtopError> {
+    read_cpu_metrics().subsystem(Subsystem::Cpu)
+}
+
+#[cfg(target_os = "macos")]
+fn read_cpu_metrics() -> Result<CpuMetrics, Box<dyn std::error::Error>> {
     let physical_cores = get_physical_cores()?;
     let logical_cores = get_logical_cores()?;
     let cpu_brand = get_cpu_brand();
@@ -80,6 +104,23 @@ pub fn get_cpu_metrics() -> Result<CpuMetrics, Box<dyn std::error::Error>> {
         }
     }
 
+    // Cluster layout from the chip profile, once the core counts are known
+    let clusters = match (ecpu_cores, pcpu_cores) {
+        (Some(ecpu), Some(pcpu)) => {
+            let (ecpu_cluster_mhz, pcpu_cluster_mhz) =
+                iokit::get_cluster_frequencies().unwrap_or_default();
+            let tables = crate::metrics::ioreport_sample::FreqTables {
+                ecpu_mhz: ecpu_freqs_mhz.clone().unwrap_or_default(),
+                pcpu_mhz: pcpu_freqs_mhz.clone().unwrap_or_default(),
+                gpu_mhz: Vec::new(),
+                ecpu_cluster_mhz,
+                pcpu_cluster_mhz,
+            };
+            crate::metrics::chip_profile::active_profile().clusters(ecpu, pcpu, &tables)
+        }
+        _ => Vec::new(),
+    };
+
     Ok(CpuMetrics {
         physical_cores,
         logical_cores,
@@ -90,9 +131,11 @@ pub fn get_cpu_metrics() -> Result<CpuMetrics, Box<dyn std::error::Error>> {
         pcpu_cores,
         ecpu_freqs_mhz,
         pcpu_freqs_mhz,
+        clusters,
     })
 }
 
+#[cfg(target_os = "macos")]
 fn get_physical_cores() -> Result<u32, Box<dyn std::error::Error>> {
     unsafe {
         // Try HW_PHYSICALCPU first
@@ -136,6 +179,7 @@ fn get_physical_cores() -> Result<u32, Box<dyn std::error::Error>> {
     }
 }
 
+#[cfg(target_os = "macos")]
 fn get_logical_cores() -> Result<u32, Box<dyn std::error::Error>> {
     unsafe {
         let mut mib = [libc::CTL_HW, libc::HW_NCPU];
@@ -159,6 +203,7 @@ fn get_logical_cores() -> Result<u32, Box<dyn std::error::Error>> {
     }
 }
 
+#[cfg(target_os = "macos")]
 fn get_cpu_brand() -> String {
     // Try fast path via sysctlbyname (Intel Macs)
     unsafe {
@@ -199,6 +244,7 @@ fn get_cpu_brand() -> String {
     chip_name.unwrap_or_else(|| "Apple Processor".to_string())
 }
 
+#[cfg(target_os = "macos")]
 fn get_cpu_frequency() -> Result<u64, Box<dyn std::error::Error>> {
     unsafe {
         let mut mib = [libc::CTL_HW, libc::HW_CPU_FREQ];
@@ -223,6 +269,7 @@ fn get_cpu_frequency() -> Result<u64, Box<dyn std::error::Error>> {
     }
 }
 
+#[cfg(target_os = "macos")]
 fn get_cpu_frequency_alt() -> u64 {
     // Try to get CPU frequency max from sysctl
     unsafe {
@@ -249,6 +296,7 @@ fn get_cpu_frequency_alt() -> u64 {
     0
 }
 
+#[cfg(target_os = "macos")]
 fn get_apple_silicon_info() -> (Option<String>, Option<u32>, Option<u32>, Option<u64>) {
     // Try to run system_profiler to get detailed chip info
     let output = Command::new("system_profiler")
@@ -299,6 +347,7 @@ fn get_apple_silicon_info() -> (Option<String>, Option<u32>, Option<u32>, Option
     (None, None, None, None)
 }
 
+#[cfg(target_os = "macos")]
 fn get_perflevel_core_counts() -> (Option<u32>, Option<u32>) {
     unsafe {
         let read_u32 = |name: &str| -> Option<u32> {
@@ -325,3 +374,68 @@ fn get_perflevel_core_counts() -> (Option<u32>, Option<u32>) {
         (ecpu, pcpu)
     }
 }
+
+#[cfg(target_os = "linux")]
+fn read_cpu_metrics() -> Result<CpuMetrics, Box<dyn std::error::Error>> {
+    use crate::utils::procfs::{read_trimmed, read_u64};
+    use std::collections::HashSet;
+
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo")?;
+
+    let mut logical_cores = 0u32;
+    let mut cores = HashSet::new();
+    let mut physical_id = "";
+    let mut cpu_brand = None;
+    let mut cpuinfo_mhz = None;
+
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "processor" => logical_cores += 1,
+            "physical id" => physical_id = value,
+            "core id" => {
+                cores.insert((physical_id, value));
+            }
+            // x86 reports "model name", most ARM kernels only "Hardware"
+            "model name" | "Hardware" if cpu_brand.is_none() => {
+                cpu_brand = Some(value.to_string());
+            }
+            "cpu MHz" if cpuinfo_mhz.is_none() => {
+                cpuinfo_mhz = value.parse::<f64>().ok().map(|mhz| mhz as u64);
+            }
+            _ => {}
+        }
+    }
+
+    if logical_cores == 0 {
+        return Err("Failed to get logical CPU count".into());
+    }
+
+    // Core ids are missing on some ARM kernels; fall back to the logical count
+    let physical_cores = if cores.is_empty() {
+        logical_cores
+    } else {
+        cores.len() as u32
+    };
+
+    // cpufreq reports kHz; /proc/cpuinfo only has the current frequency
+    let cpu_frequency_mhz = read_u64("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
+        .map(|khz| khz / 1000)
+        .or(cpuinfo_mhz)
+        .unwrap_or(0);
+
+    let cpu_brand = cpu_brand
+        .or_else(|| read_trimmed("/sys/firmware/devicetree/base/model"))
+        .unwrap_or_else(|| "Unknown Processor".to_string());
+
+    Ok(CpuMetrics {
+        physical_cores,
+        logical_cores,
+        cpu_brand,
+        cpu_frequency_mhz,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,331 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use serde::Serialize;
+use std::error::Error;
+
+// Share of scheduler ticks per state between samples, in percent (each core sums to 100)
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct CpuTimes {
+    pub user: f32,
+    pub nice: f32,
+    pub system: f32,
+    pub idle: f32,
+    pub iowait: Option<f32>,  // Linux only
+    pub irq: Option<f32>,     // Linux only
+    pub softirq: Option<f32>, // Linux only
+    pub steal: Option<f32>,   // Linux only, time taken by the hypervisor
+}
+
+impl CpuTimes {
+    /// Percent of time not idle, as `top` and `vmstat` count it (iowait is idle)
+    pub fn busy(&self) -> f32 {
+        (100.0 - self.idle - self.iowait.unwrap_or(0.0)).max(0.0)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct CpuTimeMetrics {
+    pub total: CpuTimes,      // all cores together
+    pub cores: Vec<CpuTimes>, // in OS core order
+}
+
+// Cumulative ticks of one core (or all of them)
+#[derive(Debug, Default, Clone)]
+struct CpuTicks {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: Option<u64>,
+    irq: Option<u64>,
+    softirq: Option<u64>,
+    steal: Option<u64>,
+}
+
+impl CpuTicks {
+    fn sum(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait.unwrap_or(0)
+            + self.irq.unwrap_or(0)
+            + self.softirq.unwrap_or(0)
+            + self.steal.unwrap_or(0)
+    }
+
+    fn percent_since(&self, prev: &CpuTicks) -> CpuTimes {
+        // Counters that went back (wrapped or reset) count as no ticks for that state
+        let delta = CpuTicks {
+            user: self.user.saturating_sub(prev.user),
+            nice: self.nice.saturating_sub(prev.nice),
+            system: self.system.saturating_sub(prev.system),
+            idle: self.idle.saturating_sub(prev.idle),
+            iowait: self
+                .iowait
+                .map(|t| t.saturating_sub(prev.iowait.unwrap_or(0))),
+            irq: self.irq.map(|t| t.saturating_sub(prev.irq.unwrap_or(0))),
+            softirq: self
+                .softirq
+                .map(|t| t.saturating_sub(prev.softirq.unwrap_or(0))),
+            steal: self
+                .steal
+                .map(|t| t.saturating_sub(prev.steal.unwrap_or(0))),
+        };
+        let total = delta.sum();
+        let pct = |ticks: u64| {
+            if total > 0 {
+                ticks as f32 * 100.0 / total as f32
+            } else {
+                0.0
+            }
+        };
+        CpuTimes {
+            user: pct(delta.user),
+            nice: pct(delta.nice),
+            system: pct(delta.system),
+            // No ticks at all since the last sample reads as idle, not busy
+            idle: if total > 0 { pct(delta.idle) } else { 100.0 },
+            iowait: delta.iowait.map(pct),
+            irq: delta.irq.map(pct),
+            softirq: delta.softirq.map(pct),
+            steal: delta.steal.map(pct),
+        }
+    }
+}
+
+/// Tick-based CPU time breakdown between samples, per core and aggregate
+pub struct CpuTimeSampler {
+    prev: Option<(CpuTicks, Vec<CpuTicks>)>,
+}
+
+impl CpuTimeSampler {
+    pub fn new() -> Self {
+        Self {
+            prev: read_ticks().ok(),
+        }
+    }
+
+    pub fn sample(&mut self) -> Result<CpuTimeMetrics, AtopError> {
+        let (total, cores) = read_ticks().subsystem(Subsystem::CpuTime)?;
+        // Without an earlier sample, report the averages since boot
+        let (prev_total, prev_cores) = self.prev.take().unwrap_or_default();
+
+        let metrics = CpuTimeMetrics {
+            total: total.percent_since(&prev_total),
+            cores: cores
+                .iter()
+                .enumerate()
+                .map(|(i, core)| {
+                    core.percent_since(&prev_cores.get(i).cloned().unwrap_or_default())
+                })
+                .collect(),
+        };
+
+        self.prev = Some((total, cores));
+
+        Ok(metrics)
+    }
+}
+
+impl Default for CpuTimeSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_ticks() -> Result<(CpuTicks, Vec<CpuTicks>), Box<dyn Error>> {
+    let content = std::fs::read_to_string("/proc/stat")?;
+    parse_stat_ticks(&content)
+}
+
+#[cfg(target_os = "linux")]
+fn parse_stat_ticks(content: &str) -> Result<(CpuTicks, Vec<CpuTicks>), Box<dyn Error>> {
+    let mut total = None;
+    let mut cores = Vec::new();
+
+    // "cpu  user nice system idle iowait irq softirq steal guest guest_nice", then one "cpuN" line per core
+    for line in content.lines().filter(|l| l.starts_with("cpu")) {
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap_or("");
+        let values: Vec<u64> = fields.map(|v| v.parse().unwrap_or(0)).collect();
+        if values.len() < 4 {
+            continue;
+        }
+        let ticks = CpuTicks {
+            user: values[0],
+            nice: values[1],
+            system: values[2],
+            idle: values[3],
+            iowait: values.get(4).copied(),
+            irq: values.get(5).copied(),
+            softirq: values.get(6).copied(),
+            steal: values.get(7).copied(),
+        };
+        if name == "cpu" {
+            total = Some(ticks);
+        } else {
+            cores.push(ticks);
+        }
+    }
+
+    let total = total.ok_or("no aggregate cpu line in /proc/stat")?;
+    Ok((total, cores))
+}
+
+#[cfg(target_os = "macos")]
+fn read_ticks() -> Result<(CpuTicks, Vec<CpuTicks>), Box<dyn Error>> {
+    let mut cpu_count: libc::natural_t = 0;
+    let mut info: libc::processor_info_array_t = std::ptr::null_mut();
+    let mut info_count: libc::mach_msg_type_number_t = 0;
+
+    let ret = unsafe {
+        libc::host_processor_info(
+            mach2::mach_init::mach_host_self(),
+            libc::PROCESSOR_CPU_LOAD_INFO,
+            &mut cpu_count,
+            &mut info,
+            &mut info_count,
+        )
+    };
+    if ret != mach2::kern_return::KERN_SUCCESS || info.is_null() {
+        return Err("host_processor_info failed".into());
+    }
+
+    // CPU_STATE_MAX tick counters per core; the array is allocated in our address space
+    let states = libc::CPU_STATE_MAX as usize;
+    let raw = unsafe { std::slice::from_raw_parts(info, info_count as usize) };
+    let cores: Vec<CpuTicks> = raw
+        .chunks_exact(states)
+        .take(cpu_count as usize)
+        .map(|ticks| CpuTicks {
+            user: ticks[libc::CPU_STATE_USER as usize] as u32 as u64,
+            nice: ticks[libc::CPU_STATE_NICE as usize] as u32 as u64,
+            system: ticks[libc::CPU_STATE_SYSTEM as usize] as u32 as u64,
+            idle: ticks[libc::CPU_STATE_IDLE as usize] as u32 as u64,
+            ..Default::default()
+        })
+        .collect();
+
+    unsafe {
+        libc::vm_deallocate(
+            mach2::traps::mach_task_self(),
+            info as libc::vm_address_t,
+            info_count as libc::vm_size_t * std::mem::size_of::<libc::integer_t>(),
+        );
+    }
+
+    let total = cores
+        .iter()
+        .fold(CpuTicks::default(), |acc, core| CpuTicks {
+            user: acc.user + core.user,
+            nice: acc.nice + core.nice,
+            system: acc.system + core.system,
+            idle: acc.idle + core.idle,
+            ..Default::default()
+        });
+
+    Ok((total, cores))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticks(user: u64, system: u64, idle: u64, iowait: Option<u64>) -> CpuTicks {
+        CpuTicks {
+            user,
+            system,
+            idle,
+            iowait,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_proc_stat_ticks() {
+        let (total, cores) = parse_stat_ticks(
+            "\
+cpu  4705 356 584 3699 23 23 0 0 0 0
+cpu0 2000 100 300 1800 10 20 5 7 0 0
+cpu1 2705 256 284 1899
+cpu2 12 3
+intr 114930548 113199788 3 0 5 263 0 4
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            (total.user, total.nice, total.system, total.idle),
+            (4705, 356, 584, 3699)
+        );
+        assert_eq!(
+            (total.iowait, total.irq, total.softirq),
+            (Some(23), Some(23), Some(0))
+        );
+        // The short cpu2 line is skipped
+        assert_eq!(cores.len(), 2);
+        assert_eq!(cores[0].steal, Some(7));
+        // Kernels without the iowait/irq/steal columns leave them unset
+        assert_eq!(cores[1].idle, 1899);
+        assert_eq!(
+            (cores[1].iowait, cores[1].irq, cores[1].steal),
+            (None, None, None)
+        );
+
+        assert!(parse_stat_ticks("cpu0 1 2 3 4\n").is_err());
+    }
+
+    #[test]
+    fn shares_cover_the_ticks_since_the_previous_sample() {
+        let prev = ticks(100, 50, 800, Some(50));
+        let times = ticks(160, 70, 900, Some(70)).percent_since(&prev);
+        assert_eq!((times.user, times.system), (30.0, 10.0));
+        assert_eq!((times.idle, times.iowait), (50.0, Some(10.0)));
+        assert_eq!(times.irq, None);
+        assert_eq!(times.busy(), 40.0);
+
+        // A counter that went back counts no ticks; the others still add up to 100
+        let times = ticks(10, 70, 830, Some(50)).percent_since(&prev);
+        assert_eq!((times.user, times.system, times.idle), (0.0, 40.0, 60.0));
+
+        // No ticks since the previous sample is idle, not busy
+        let times = prev.percent_since(&prev);
+        assert_eq!(
+            (times.user, times.idle, times.iowait),
+            (0.0, 100.0, Some(0.0))
+        );
+        assert_eq!(times.busy(), 0.0);
+    }
+
+    #[test]
+    fn busy_counts_iowait_as_idle() {
+        let times = CpuTimes {
+            user: 30.0,
+            system: 10.0,
+            idle: 40.0,
+            iowait: Some(20.0),
+            ..Default::default()
+        };
+        assert_eq!(times.busy(), 40.0);
+        // macOS has no iowait
+        assert_eq!(
+            CpuTimes {
+                iowait: None,
+                ..times.clone()
+            }
+            .busy(),
+            60.0
+        );
+        // Rounding never makes it negative
+        assert_eq!(
+            CpuTimes {
+                idle: 90.0,
+                ..times
+            }
+            .busy(),
+            0.0
+        );
+    }
+}
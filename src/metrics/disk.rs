@@ -0,0 +1,240 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Instant;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct DiskMetrics {
+    pub name: String,
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
+    pub read_iops: f32,
+    pub write_iops: f32,
+    pub busy_percent: f32, // 0.0 - 100.0
+}
+
+// Cumulative per-device counters as reported by the OS
+#[derive(Debug, Default, Clone)]
+struct DiskCounters {
+    read_bytes: u64,
+    write_bytes: u64,
+    reads: u64,
+    writes: u64,
+    busy_ms: u64, // time with I/O in flight (Linux io_ticks)
+    // macOS only has per-direction totals, which overlap each other; the larger of the two
+    // deltas stands in for busy time
+    read_time_ms: u64,
+    write_time_ms: u64,
+}
+
+/// Turns cumulative block device counters into per-second rates between samples
+pub struct DiskSampler {
+    prev: HashMap<String, DiskCounters>,
+    prev_time: Instant,
+}
+
+impl DiskSampler {
+    pub fn new() -> Self {
+        Self {
+            prev: read_disk_counters().unwrap_or_default(),
+            prev_time: Instant::now(),
+        }
+    }
+
+    /// Rates since the previous call (or since construction for the first call)
+    pub fn sample(&mut self) -> Result<Vec<DiskMetrics>, AtopError> {
+        let counters = read_disk_counters().subsystem(Subsystem::Disk)?;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.prev_time).as_secs_f64();
+
+        let mut disks: Vec<DiskMetrics> = counters
+            .iter()
+            .map(|(name, cur)| {
+                let prev = self.prev.get(name).unwrap_or(cur);
+                disk_rates(name, prev, cur, elapsed)
+            })
+            .collect();
+        disks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.prev = counters;
+        self.prev_time = now;
+
+        Ok(disks)
+    }
+}
+
+impl Default for DiskSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn disk_rates(name: &str, prev: &DiskCounters, cur: &DiskCounters, elapsed: f64) -> DiskMetrics {
+    let rate = |cur: u64, prev: u64| {
+        if elapsed > 0.0 {
+            cur.saturating_sub(prev) as f64 / elapsed
+        } else {
+            0.0
+        }
+    };
+    let busy_ms_per_sec = rate(cur.busy_ms, prev.busy_ms)
+        .max(rate(cur.read_time_ms, prev.read_time_ms))
+        .max(rate(cur.write_time_ms, prev.write_time_ms));
+    DiskMetrics {
+        name: name.to_string(),
+        read_bytes_per_sec: rate(cur.read_bytes, prev.read_bytes) as u64,
+        write_bytes_per_sec: rate(cur.write_bytes, prev.write_bytes) as u64,
+        read_iops: rate(cur.reads, prev.reads) as f32,
+        write_iops: rate(cur.writes, prev.writes) as f32,
+        // Queued requests can still add up to more than wall time on macOS
+        busy_percent: (busy_ms_per_sec / 10.0).min(100.0) as f32,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_disk_counters() -> Result<HashMap<String, DiskCounters>, Box<dyn Error>> {
+    use crate::metrics::iokit::{IOServiceIterator, get_io_child, get_io_props, io_object_release};
+    use crate::utils::iokit_utils::{cf_dict_get_dict, cf_dict_get_i64, cf_dict_get_string};
+    use core_foundation::base::CFRelease;
+
+    let mut disks = HashMap::new();
+
+    for (entry, _) in IOServiceIterator::new("IOBlockStorageDriver")? {
+        // The BSD name (disk0, disk1, ...) lives on the IOMedia child of the driver
+        let name = get_io_child(entry).and_then(|child| {
+            let name = get_io_props(child).ok().map(|props| {
+                let name = cf_dict_get_string(props, "BSD Name");
+                unsafe { CFRelease(props as _) };
+                name
+            });
+            io_object_release(child);
+            name.flatten()
+        });
+
+        let props = get_io_props(entry);
+        io_object_release(entry);
+        let (Some(name), Ok(props)) = (name, props) else {
+            continue;
+        };
+
+        if let Some(stats) = cf_dict_get_dict(props, "Statistics") {
+            let stat = |key: &str| cf_dict_get_i64(stats, key).unwrap_or(0).max(0) as u64;
+            disks.insert(
+                name,
+                DiskCounters {
+                    read_bytes: stat("Bytes (Read)"),
+                    write_bytes: stat("Bytes (Write)"),
+                    reads: stat("Operations (Read)"),
+                    writes: stat("Operations (Write)"),
+                    // Total times are in nanoseconds
+                    read_time_ms: stat("Total Time (Read)") / 1_000_000,
+                    write_time_ms: stat("Total Time (Write)") / 1_000_000,
+                    ..Default::default()
+                },
+            );
+        }
+
+        unsafe { CFRelease(props as _) };
+    }
+
+    Ok(disks)
+}
+
+#[cfg(target_os = "linux")]
+fn read_disk_counters() -> Result<HashMap<String, DiskCounters>, Box<dyn Error>> {
+    let content = std::fs::read_to_string("/proc/diskstats")?;
+    // Whole devices only: partitions are not listed under /sys/block
+    Ok(parse_diskstats(&content, |name| {
+        std::path::Path::new("/sys/block").join(name).exists()
+    }))
+}
+
+#[cfg(target_os = "linux")]
+fn parse_diskstats(
+    content: &str,
+    is_whole_disk: impl Fn(&str) -> bool,
+) -> HashMap<String, DiskCounters> {
+    const SECTOR_SIZE: u64 = 512;
+
+    let mut disks = HashMap::new();
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 14 {
+            continue;
+        }
+        let name = fields[2];
+        if name.starts_with("loop") || name.starts_with("ram") || !is_whole_disk(name) {
+            continue;
+        }
+
+        let field = |i: usize| fields[i].parse::<u64>().unwrap_or(0);
+        disks.insert(
+            name.to_string(),
+            DiskCounters {
+                reads: field(3),
+                read_bytes: field(5) * SECTOR_SIZE,
+                writes: field(7),
+                write_bytes: field(9) * SECTOR_SIZE,
+                busy_ms: field(12),
+                ..Default::default()
+            },
+        );
+    }
+    disks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_whole_disks_from_diskstats() {
+        let content = "\
+ 259       0 nvme0n1 1000 20 8000 300 500 10 4000 200 0 700 500 0 0 0 0
+ 259       1 nvme0n1p1 900 20 7000 250 400 10 3000 150 0 600 400 0 0 0 0
+   7       0 loop0 50 0 100 5 0 0 0 0 0 5 5 0 0 0 0
+   8       0 sda 10 0 16
+";
+        let disks = parse_diskstats(content, |name| !name.contains('p'));
+        assert_eq!(disks.len(), 1);
+        let nvme = &disks["nvme0n1"];
+        assert_eq!((nvme.reads, nvme.writes), (1000, 500));
+        assert_eq!(nvme.read_bytes, 8000 * 512);
+        assert_eq!(nvme.write_bytes, 4000 * 512);
+        assert_eq!(nvme.busy_ms, 700);
+    }
+
+    #[test]
+    fn rates_cover_the_elapsed_time() {
+        let prev = DiskCounters {
+            read_bytes: 1 << 20,
+            reads: 10,
+            busy_ms: 1000,
+            ..Default::default()
+        };
+        let cur = DiskCounters {
+            read_bytes: 3 << 20,
+            reads: 30,
+            writes: 4,
+            busy_ms: 2000,
+            ..Default::default()
+        };
+        let disk = disk_rates("sda", &prev, &cur, 2.0);
+        assert_eq!(disk.read_bytes_per_sec, 1 << 20);
+        assert_eq!((disk.read_iops, disk.write_iops), (10.0, 2.0));
+        assert_eq!(disk.busy_percent, 50.0);
+    }
+
+    #[test]
+    fn overlapping_read_and_write_time_does_not_add_up() {
+        let prev = DiskCounters::default();
+        let cur = DiskCounters {
+            read_time_ms: 600,
+            write_time_ms: 700,
+            ..Default::default()
+        };
+        assert_eq!(disk_rates("disk0", &prev, &cur, 1.0).busy_percent, 70.0);
+    }
+}
@@ -0,0 +1,488 @@
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::metrics::chip_profile::{ChipProfile, ChipProfiles, matches_any, override_path};
+use crate::metrics::error::{AtopError, Subsystem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn, // the subsystem works with less detail, or is optional on this machine
+    Fail, // the subsystem will be empty
+}
+
+/// One probe of a data source the collectors depend on
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub area: String,  // subsystem name, or "ioreport" / "chip_profile"
+    pub probe: String, // what was read, e.g. "sysctl hw.memsize" or "/proc/meminfo"
+    pub status: CheckStatus,
+    pub detail: String, // what was found, or the error
+    pub hint: Option<String>,
+    pub elapsed_ms: f64,
+}
+
+// Time `f`; on error the check gets `status_on_error` and the hint
+fn probe(
+    area: &str,
+    probe: &str,
+    status_on_error: CheckStatus,
+    hint: &str,
+    f: impl FnOnce() -> Result<String, String>,
+) -> Check {
+    let start = Instant::now();
+    let result = f();
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let (status, detail, hint) = match result {
+        Ok(detail) => (CheckStatus::Pass, detail, None),
+        Err(detail) => (status_on_error, detail, Some(hint.to_string())),
+    };
+    Check {
+        area: area.to_string(),
+        probe: probe.to_string(),
+        status,
+        detail,
+        hint,
+        elapsed_ms,
+    }
+}
+
+/// Probe every data source on this platform, in roughly the order the collectors use them;
+/// `cgroup_root` is the hierarchy the cgroup collector would read (Linux only)
+pub fn run_checks(cgroup_root: &str) -> Vec<Check> {
+    let mut checks = platform_checks(cgroup_root);
+    let brand = crate::metrics::get_cpu_metrics()
+        .map(|cpu| cpu.cpu_brand)
+        .unwrap_or_default();
+    checks.push(chip_profile_check(&brand));
+    checks.extend(ioreport_checks(&brand));
+    checks
+}
+
+#[cfg(target_os = "macos")]
+fn platform_checks(_cgroup_root: &str) -> Vec<Check> {
+    use crate::metrics::iokit;
+    use crate::utils::iokit_utils::{sysctl_bytes, sysctl_string, sysctl_u64};
+    use CheckStatus::{Fail, Warn};
+
+    type Reader = fn(&str) -> Result<String, String>;
+    let number: Reader = |key| {
+        sysctl_u64(key)
+            .map(|v| v.to_string())
+            .map_err(|e| e.to_string())
+    };
+    let text: Reader = |key| sysctl_string(key).map_err(|e| e.to_string());
+    // vm.swapusage is a struct xsw_usage, not a plain number
+    let raw: Reader = |key| {
+        sysctl_bytes(key)
+            .map(|bytes| format!("{} bytes", bytes.len()))
+            .map_err(|e| e.to_string())
+    };
+    let perflevel_hint =
+        "needs macOS 12 on Apple Silicon; E/P core counts fall back to system_profiler";
+    let sysctls: [(&str, &str, Reader, CheckStatus, &str); 8] = [
+        (
+            "cpu",
+            "hw.physicalcpu",
+            number,
+            Fail,
+            "core counts come from this key",
+        ),
+        (
+            "cpu",
+            "hw.logicalcpu",
+            number,
+            Fail,
+            "core counts come from this key",
+        ),
+        (
+            "cpu",
+            "machdep.cpu.brand_string",
+            text,
+            Warn,
+            "the chip name and chip profile come from this key",
+        ),
+        (
+            "cpu",
+            "hw.perflevel0.physicalcpu",
+            number,
+            Warn,
+            perflevel_hint,
+        ),
+        (
+            "cpu",
+            "hw.perflevel1.physicalcpu",
+            number,
+            Warn,
+            perflevel_hint,
+        ),
+        (
+            "memory",
+            "hw.memsize",
+            number,
+            Fail,
+            "total RAM comes from this key",
+        ),
+        (
+            "memory",
+            "vm.swapusage",
+            raw,
+            Warn,
+            "swap usage comes from this key",
+        ),
+        (
+            "pressure",
+            "kern.memorystatus_vm_pressure_level",
+            number,
+            Warn,
+            "the memory pressure level comes from this key",
+        ),
+    ];
+    let mut checks: Vec<Check> = sysctls
+        .iter()
+        .map(|&(area, key, read, status, hint)| {
+            probe(area, &format!("sysctl {}", key), status, hint, || read(key))
+        })
+        .collect();
+
+    checks.push(probe(
+        "performance",
+        "IORegistry AppleARMIODevice pmgr",
+        Fail,
+        "no power manager entry (Intel Mac or virtual machine): frequencies stay at 0",
+        || {
+            let found = iokit::IOServiceIterator::new("AppleARMIODevice")
+                .map_err(|e| e.to_string())?
+                .any(|(entry, name)| {
+                    iokit::io_object_release(entry);
+                    name == "pmgr"
+                });
+            if found {
+                Ok("found".to_string())
+            } else {
+                Err("not found".to_string())
+            }
+        },
+    ));
+
+    let profile = crate::metrics::chip_profile::active_profile();
+    let table = |freqs: Option<Vec<u32>>| match freqs {
+        Some(freqs) if !freqs.is_empty() => Ok(format!(
+            "{} states, {} - {} MHz",
+            freqs.len(),
+            freqs.first().unwrap(),
+            freqs.last().unwrap()
+        )),
+        _ => Err("missing".to_string()),
+    };
+    let hint = "the chip profile names a pmgr key this machine doesn't have; \
+                override it in the chip profile file";
+    let cpu = iokit::get_cpu_frequencies();
+    let (ecpu, pcpu) = match cpu {
+        Ok((ecpu, pcpu, _)) => (ecpu, pcpu),
+        Err(_) => (None, None),
+    };
+    checks.push(probe(
+        "performance",
+        &format!("pmgr {} (E-cores)", profile.ecpu_dvfs_key),
+        Warn,
+        hint,
+        || table(ecpu),
+    ));
+    checks.push(probe(
+        "performance",
+        &format!("pmgr {} (P-cores)", profile.pcpu_dvfs_key),
+        Warn,
+        hint,
+        || table(pcpu),
+    ));
+    checks.push(probe(
+        "performance",
+        &format!("pmgr {} (GPU)", profile.gpu_dvfs_key),
+        Warn,
+        hint,
+        || {
+            table(
+                iokit::get_gpu_frequencies()
+                    .ok()
+                    .and_then(|(_, gpu, _)| gpu),
+            )
+        },
+    ));
+    checks
+}
+
+#[cfg(target_os = "linux")]
+fn platform_checks(cgroup_root: &str) -> Vec<Check> {
+    use CheckStatus::{Fail, Warn};
+
+    let controllers = std::path::Path::new(cgroup_root).join("cgroup.controllers");
+    let controllers = controllers.to_string_lossy();
+    let paths: [(&str, &str, CheckStatus, &str); 14] = [
+        (
+            "cpu",
+            "/proc/cpuinfo",
+            Fail,
+            "core counts and the CPU brand come from this file",
+        ),
+        (
+            "cpu",
+            "/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq",
+            Warn,
+            "no cpufreq driver (common in VMs): the max frequency falls back to /proc/cpuinfo",
+        ),
+        (
+            "cpu_time",
+            "/proc/stat",
+            Fail,
+            "CPU time and load counters come from this file; is /proc mounted?",
+        ),
+        (
+            "memory",
+            "/proc/meminfo",
+            Fail,
+            "RAM and swap usage come from this file; is /proc mounted?",
+        ),
+        (
+            "memory",
+            "/proc/vmstat",
+            Fail,
+            "paging rates come from this file",
+        ),
+        (
+            "disk",
+            "/proc/diskstats",
+            Fail,
+            "disk I/O rates come from this file",
+        ),
+        (
+            "network",
+            "/proc/net/dev",
+            Fail,
+            "interface counters come from this file",
+        ),
+        (
+            "process",
+            "/proc",
+            Fail,
+            "the process list comes from /proc/<pid>",
+        ),
+        (
+            "filesystem",
+            "/proc/self/mounts",
+            Fail,
+            "the mount list comes from this file",
+        ),
+        (
+            "pressure",
+            "/proc/pressure/cpu",
+            Warn,
+            "kernel without CONFIG_PSI or booted with psi=0",
+        ),
+        (
+            "pressure",
+            "/proc/pressure/memory",
+            Warn,
+            "kernel without CONFIG_PSI or booted with psi=0",
+        ),
+        (
+            "pressure",
+            "/proc/pressure/io",
+            Warn,
+            "kernel without CONFIG_PSI or booted with psi=0",
+        ),
+        (
+            "cgroup",
+            &controllers,
+            Warn,
+            "not a cgroup v2 hierarchy; pass the one to aggregate with --cgroup-root",
+        ),
+        (
+            "battery",
+            "/sys/class/power_supply",
+            Warn,
+            "no power supply class (desktops, VMs, containers): no battery panel",
+        ),
+    ];
+    paths
+        .iter()
+        .map(|&(area, path, status, hint)| {
+            probe(area, path, status, hint, || {
+                let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+                if meta.is_dir() {
+                    let entries = std::fs::read_dir(path).map_err(|e| e.to_string())?.count();
+                    Ok(format!("{} entries", entries))
+                } else {
+                    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+                    Ok(format!("{} lines", content.lines().count()))
+                }
+            })
+        })
+        .collect()
+}
+
+// A broken override file is ignored by the collectors, so only warn about it
+fn chip_profile_check(brand: &str) -> Check {
+    let path = override_path().filter(|path| path.exists());
+    let name = match &path {
+        Some(path) => format!("overrides {}", path.display()),
+        None => "built-in profiles".to_string(),
+    };
+    probe(
+        "chip_profile",
+        &name,
+        CheckStatus::Warn,
+        "fix or remove the file; the collectors use the built-in profiles meanwhile",
+        || {
+            let profile = ChipProfiles::load()
+                .map_err(|e| e.reason().to_string())?
+                .select(brand);
+            if profile.name.is_empty() {
+                Ok(format!("generic profile for \"{}\"", brand))
+            } else {
+                Ok(format!("profile \"{}\" for \"{}\"", profile.name, brand))
+            }
+        },
+    )
+}
+
+// Subscribe to each IOReport group, take one short sample and check the chip profile's
+// channel names match something in it
+fn ioreport_checks(brand: &str) -> Vec<Check> {
+    let profile = ChipProfiles::load()
+        .unwrap_or_else(|_| ChipProfiles::builtin())
+        .select(brand);
+
+    type Patterns = fn(&ChipProfile) -> Vec<(&'static str, &Vec<String>)>;
+    let groups: [(&str, Subsystem, Patterns); 3] = [
+        ("Energy Model", Subsystem::Power, |p| {
+            vec![("cpu_energy", &p.cpu_energy), ("gpu_energy", &p.gpu_energy)]
+        }),
+        ("CPU Stats", Subsystem::Performance, |p| {
+            vec![
+                ("ecpu_states", &p.ecpu_states),
+                ("pcpu_states", &p.pcpu_states),
+            ]
+        }),
+        ("GPU Stats", Subsystem::Performance, |p| {
+            vec![("gpu_states", &p.gpu_states)]
+        }),
+    ];
+
+    let mut checks = Vec::new();
+    for (group, subsystem, patterns) in groups {
+        let start = Instant::now();
+        let sample = crate::metrics::IOReport::new(vec![(group, None)])
+            .and_then(|ioreport| ioreport.sample_power(10));
+        let mut check = Check {
+            area: "ioreport".to_string(),
+            probe: format!("IOReport group \"{}\" ({})", group, subsystem),
+            status: CheckStatus::Pass,
+            detail: String::new(),
+            hint: None,
+            elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        };
+        match sample {
+            Ok(sample) => {
+                check.detail = format!("{} channels", sample.channels.len());
+                let unmatched: Vec<&str> = patterns(&profile)
+                    .into_iter()
+                    .filter(|(_, patterns)| {
+                        !sample
+                            .channels
+                            .iter()
+                            .any(|channel| matches_any(patterns, &channel.name))
+                    })
+                    .map(|(field, _)| field)
+                    .collect();
+                if !unmatched.is_empty() {
+                    check.status = CheckStatus::Warn;
+                    check.detail.push_str(&format!(
+                        ", no channel matches the profile's {}",
+                        unmatched.join(", ")
+                    ));
+                    check.hint = Some(
+                        "run `atop debug dump-channels` and adjust the chip profile".to_string(),
+                    );
+                }
+            }
+            // Not an error on other platforms, but it's why the panels are empty
+            Err(err @ AtopError::Unsupported { .. }) => {
+                check.status = CheckStatus::Warn;
+                check.detail = err.reason().to_string();
+                check.hint = Some(format!("{} panels stay empty on this platform", subsystem));
+            }
+            Err(err) => {
+                check.status = CheckStatus::Fail;
+                check.detail = err.reason().to_string();
+                check.hint = Some(
+                    "IOReport is missing or blocked (VM, sandbox); the panels stay empty"
+                        .to_string(),
+                );
+            }
+        }
+        checks.push(check);
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_maps_results_to_status_detail_and_hint() {
+        let passed = probe("memory", "/proc/meminfo", CheckStatus::Fail, "hint", || {
+            Ok("52 lines".to_string())
+        });
+        assert_eq!(
+            (passed.area.as_str(), passed.probe.as_str()),
+            ("memory", "/proc/meminfo")
+        );
+        assert_eq!(passed.status, CheckStatus::Pass);
+        assert_eq!(passed.detail, "52 lines");
+        assert_eq!(passed.hint, None);
+
+        let failed = probe(
+            "pressure",
+            "/proc/pressure/io",
+            CheckStatus::Warn,
+            "no PSI",
+            || Err("No such file or directory".to_string()),
+        );
+        assert_eq!(failed.status, CheckStatus::Warn);
+        assert_eq!(failed.detail, "No such file or directory");
+        assert_eq!(failed.hint.as_deref(), Some("no PSI"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn probes_the_given_cgroup_root() {
+        let cgroup_check = |root: &std::path::Path| {
+            platform_checks(&root.to_string_lossy())
+                .into_iter()
+                .find(|c| c.area == "cgroup")
+                .unwrap()
+        };
+
+        let root = crate::utils::procfs::fixture_dir(
+            "doctor-cgroup",
+            &[("cgroup.controllers", "cpu io memory pids\n")],
+        );
+        let check = cgroup_check(&root);
+        assert_eq!(
+            check.probe,
+            root.join("cgroup.controllers").to_string_lossy()
+        );
+        assert_eq!(
+            (check.status, check.detail.as_str()),
+            (CheckStatus::Pass, "1 lines")
+        );
+
+        let check = cgroup_check(&root.join("missing"));
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check.hint.unwrap().contains("--cgroup-root"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,160 @@
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// The parts of the system atop collects from, one per metrics module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Cpu,
+    CpuTime,
+    Load,
+    Memory,
+    Power,
+    Performance,
+    Battery,
+    Disk,
+    Network,
+    Process,
+    Cgroup,
+    Pressure,
+    Filesystem,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 13] = [
+        Subsystem::Cpu,
+        Subsystem::CpuTime,
+        Subsystem::Load,
+        Subsystem::Memory,
+        Subsystem::Power,
+        Subsystem::Performance,
+        Subsystem::Battery,
+        Subsystem::Disk,
+        Subsystem::Network,
+        Subsystem::Process,
+        Subsystem::Cgroup,
+        Subsystem::Pressure,
+        Subsystem::Filesystem,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Cpu => "cpu",
+            Subsystem::CpuTime => "cpu_time",
+            Subsystem::Load => "load",
+            Subsystem::Memory => "memory",
+            Subsystem::Power => "power",
+            Subsystem::Performance => "performance",
+            Subsystem::Battery => "battery",
+            Subsystem::Disk => "disk",
+            Subsystem::Network => "network",
+            Subsystem::Process => "process",
+            Subsystem::Cgroup => "cgroup",
+            Subsystem::Pressure => "pressure",
+            Subsystem::Filesystem => "filesystem",
+        }
+    }
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Why a subsystem has no data, so "0 W" can be told apart from "not supported"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AtopError {
+    // Not implemented on this platform
+    Unsupported {
+        subsystem: Subsystem,
+    },
+    // The source exists on this platform but couldn't be opened (IOReport subscription,
+    // missing procfs file, permissions)
+    Unavailable {
+        subsystem: Subsystem,
+        reason: String,
+    },
+    // The source is open but a read failed or returned data we can't interpret
+    Read {
+        subsystem: Subsystem,
+        reason: String,
+    },
+}
+
+impl AtopError {
+    pub fn unavailable(subsystem: Subsystem, reason: impl fmt::Display) -> Self {
+        AtopError::Unavailable {
+            subsystem,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn read(subsystem: Subsystem, reason: impl fmt::Display) -> Self {
+        AtopError::Read {
+            subsystem,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        match self {
+            AtopError::Unsupported { .. } => "not supported on this platform",
+            AtopError::Unavailable { reason, .. } | AtopError::Read { reason, .. } => reason,
+        }
+    }
+
+    pub fn subsystem(&self) -> Subsystem {
+        match self {
+            AtopError::Unsupported { subsystem }
+            | AtopError::Unavailable { subsystem, .. }
+            | AtopError::Read { subsystem, .. } => *subsystem,
+        }
+    }
+
+    /// Classify a low-level error; missing files and denied access mean the source is unavailable
+    pub(crate) fn from_source(subsystem: Subsystem, err: Box<dyn Error>) -> Self {
+        let err = match err.downcast::<AtopError>() {
+            Ok(typed) => return *typed,
+            Err(err) => err,
+        };
+        match err.downcast_ref::<std::io::Error>().map(|e| e.kind()) {
+            Some(std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied) => {
+                Self::unavailable(subsystem, err)
+            }
+            _ => Self::read(subsystem, err),
+        }
+    }
+}
+
+impl fmt::Display for AtopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.subsystem(), self.reason())
+    }
+}
+
+impl Error for AtopError {}
+
+/// The value of a subsystem read, or None with the error added to `unavailable`
+pub fn available<T>(result: Result<T, AtopError>, unavailable: &mut Vec<AtopError>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            unavailable.push(e);
+            None
+        }
+    }
+}
+
+/// Tag the errors of the private readers with the subsystem they belong to
+pub(crate) trait ResultExt<T> {
+    fn subsystem(self, subsystem: Subsystem) -> Result<T, AtopError>;
+}
+
+impl<T> ResultExt<T> for Result<T, Box<dyn Error>> {
+    fn subsystem(self, subsystem: Subsystem) -> Result<T, AtopError> {
+        self.map_err(|e| AtopError::from_source(subsystem, e))
+    }
+}
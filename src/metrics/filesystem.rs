@@ -0,0 +1,254 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use serde::Serialize;
+use std::error::Error;
+
+// Usage above this share of space or inodes is flagged
+pub const DEFAULT_FS_WARN_PERCENT: f32 = 90.0;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct FilesystemMetrics {
+    pub mount_point: String,
+    pub device: String,
+    pub fs_type: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub free_bytes: u64, // available to unprivileged users
+    pub total_inodes: u64,
+    pub used_inodes: u64,
+    pub free_inodes: u64,
+    pub used_percent: f32, // of the space usable by unprivileged users, as `df` reports
+    pub inodes_used_percent: f32, // 0 when the filesystem has no fixed inode count
+    pub warning: bool,     // space or inodes above the threshold
+}
+
+// Kernel interfaces and memory-backed filesystems; tmpfs is already part of memory usage
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rpc_pipefs",
+    "securityfs",
+    "selinuxfs",
+    "squashfs", // read-only images, always full
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+struct Mount {
+    device: String,
+    mount_point: String,
+    fs_type: String,
+}
+
+/// Mounted volumes sorted by mount point, `warn_percent` sets the `warning` flag
+pub fn get_filesystem_metrics(warn_percent: f32) -> Result<Vec<FilesystemMetrics>, AtopError> {
+    let mounts = read_mounts().subsystem(Subsystem::Filesystem)?;
+    // Unreadable mount points (permissions, stale network mounts) are skipped
+    let mut filesystems: Vec<FilesystemMetrics> = real_mounts(mounts)
+        .into_iter()
+        .filter_map(|mount| statvfs(mount, warn_percent))
+        .filter(|fs| fs.total_bytes > 0)
+        .collect();
+    filesystems.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+
+    Ok(filesystems)
+}
+
+// Drop pseudo filesystems and later mounts over an already listed mount point
+fn real_mounts(mounts: Vec<Mount>) -> Vec<Mount> {
+    let mut real: Vec<Mount> = Vec::new();
+    for mount in mounts {
+        if !PSEUDO_FILESYSTEMS.contains(&mount.fs_type.as_str())
+            && !real.iter().any(|m| m.mount_point == mount.mount_point)
+        {
+            real.push(mount);
+        }
+    }
+    real
+}
+
+fn statvfs(mount: Mount, warn_percent: f32) -> Option<FilesystemMetrics> {
+    let path = std::ffi::CString::new(mount.mount_point.as_str()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let block_size = stat.f_frsize as u64;
+    let total_bytes = stat.f_blocks as u64 * block_size;
+    let used_bytes = (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * block_size;
+    let free_bytes = stat.f_bavail as u64 * block_size;
+    let total_inodes = stat.f_files as u64;
+    let free_inodes = stat.f_ffree as u64;
+    let used_inodes = total_inodes.saturating_sub(free_inodes);
+
+    // Blocks reserved for root count as neither used nor available
+    let usable = used_bytes + free_bytes;
+    let used_percent = if usable > 0 {
+        used_bytes as f32 / usable as f32 * 100.0
+    } else {
+        0.0
+    };
+    let inodes_used_percent = if total_inodes > 0 {
+        used_inodes as f32 / total_inodes as f32 * 100.0
+    } else {
+        0.0
+    };
+
+    Some(FilesystemMetrics {
+        mount_point: mount.mount_point,
+        device: mount.device,
+        fs_type: mount.fs_type,
+        total_bytes,
+        used_bytes,
+        free_bytes,
+        total_inodes,
+        used_inodes,
+        free_inodes,
+        used_percent,
+        inodes_used_percent,
+        warning: used_percent >= warn_percent || inodes_used_percent >= warn_percent,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn read_mounts() -> Result<Vec<Mount>, Box<dyn Error>> {
+    let content = std::fs::read_to_string("/proc/self/mounts")?;
+    Ok(parse_mounts(&content))
+}
+
+// "device mount_point fs_type options dump pass", spaces escaped as \040
+#[cfg(target_os = "linux")]
+fn parse_mounts(content: &str) -> Vec<Mount> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(Mount {
+                device: unescape_mount_field(fields.next()?),
+                mount_point: unescape_mount_field(fields.next()?),
+                fs_type: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && i + 3 < bytes.len()
+            && let Some(c) = std::str::from_utf8(&bytes[i + 1..i + 4])
+                .ok()
+                .and_then(|octal| u8::from_str_radix(octal, 8).ok())
+        {
+            out.push(c);
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(target_os = "macos")]
+fn read_mounts() -> Result<Vec<Mount>, Box<dyn Error>> {
+    let mut stats: *mut libc::statfs = std::ptr::null_mut();
+    // The buffer is owned by libc and reused by the next call
+    let count = unsafe { libc::getmntinfo(&mut stats, libc::MNT_NOWAIT) };
+    if count <= 0 || stats.is_null() {
+        return Err("getmntinfo failed".into());
+    }
+    let stats = unsafe { std::slice::from_raw_parts(stats, count as usize) };
+
+    let text = |chars: &[libc::c_char]| unsafe {
+        std::ffi::CStr::from_ptr(chars.as_ptr())
+            .to_string_lossy()
+            .into_owned()
+    };
+    Ok(stats
+        .iter()
+        // Hidden APFS system volumes (VM, Preboot, ...) share the data volume's container
+        .filter(|s| s.f_flags & libc::MNT_DONTBROWSE as u32 == 0)
+        .map(|s| Mount {
+            device: text(&s.f_mntfromname),
+            mount_point: text(&s.f_mntonname),
+            fs_type: text(&s.f_fstypename),
+        })
+        .collect())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescapes_octal_mount_fields() {
+        assert_eq!(unescape_mount_field("/mnt/My\\040Drive"), "/mnt/My Drive");
+        assert_eq!(unescape_mount_field("/mnt/tab\\011name"), "/mnt/tab\tname");
+        assert_eq!(
+            unescape_mount_field("/mnt/back\\134slash"),
+            "/mnt/back\\slash"
+        );
+        assert_eq!(unescape_mount_field("/mnt/end\\040"), "/mnt/end ");
+        // Backslashes not followed by three octal digits are kept
+        assert_eq!(unescape_mount_field("/mnt/a\\b\\09"), "/mnt/a\\b\\09");
+    }
+
+    #[test]
+    fn skips_pseudo_filesystems_and_repeated_mount_points() {
+        let mounts = parse_mounts(
+            "\
+sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+tmpfs /run tmpfs rw,nosuid,nodev,size=1608356k,mode=755 0 0
+cgroup2 /sys/fs/cgroup cgroup2 rw,nosuid,nodev,noexec,relatime 0 0
+/dev/loop0 /snap/core/1 squashfs ro,nodev,relatime 0 0
+/dev/sda1 /mnt/My\\040Drive vfat rw,relatime 0 0
+/dev/sdb1 /mnt/My\\040Drive exfat rw,relatime 0 0
+short line
+",
+        );
+        assert_eq!(mounts.len(), 8);
+
+        let real = real_mounts(mounts);
+        let listed: Vec<(&str, &str, &str)> = real
+            .iter()
+            .map(|m| {
+                (
+                    m.device.as_str(),
+                    m.mount_point.as_str(),
+                    m.fs_type.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("/dev/nvme0n1p2", "/", "ext4"),
+                ("/dev/sda1", "/mnt/My Drive", "vfat"),
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use core_foundation::array::{CFArrayGetCount, CFArrayGetValueAtIndex, CFArrayRef};
+use core_foundation::array::{CFArrayGetCount, CFArrayGetValueAtIndex};
 use core_foundation::base::TCFType;
 use core_foundation::base::{CFAllocatorRef, CFRelease, CFTypeRef, kCFAllocatorDefault};
 use core_foundation::data::{CFDataGetBytes, CFDataGetLength};
@@ -7,12 +7,16 @@ use core_foundation::dictionary::{
 };
 use core_foundation::string::CFStringRef;
 use core_foundation_sys::base::CFRange;
-use serde::Serialize;
 use std::ffi::{CString, c_void};
 use std::marker::{PhantomData, PhantomPinned};
 use std::mem::MaybeUninit;
 use std::ptr::null;
 
+use crate::metrics::error::{AtopError, Subsystem};
+use crate::metrics::ioreport_sample::{
+    self, ChannelDump, ChannelState, IOReportChannel, IOReportSample,
+};
+use crate::metrics::power::PowerMetrics;
 use crate::utils::iokit_utils::{
     cf_dict_get_array, cf_dict_get_data, cf_string, cf_string_to_rust,
 };
@@ -28,6 +32,7 @@ unsafe extern "C" {
     ) -> i32;
     fn IOIteratorNext(iterator: u32) -> u32;
     fn IORegistryEntryGetName(entry: u32, name: *mut i8) -> i32;
+    fn IORegistryEntryGetChildEntry(entry: u32, plane: *const i8, child: *mut u32) -> i32;
     fn IORegistryEntryCreateCFProperties(
         entry: u32,
         properties: *mut CFMutableDictionaryRef,
@@ -77,16 +82,18 @@ unsafe extern "C" {
     fn IOReportChannelGetGroup(a: CFDictionaryRef) -> CFStringRef;
     fn IOReportChannelGetSubGroup(a: CFDictionaryRef) -> CFStringRef;
     fn IOReportChannelGetChannelName(a: CFDictionaryRef) -> CFStringRef;
+    fn IOReportChannelGetFormat(a: CFDictionaryRef) -> u8;
     fn IOReportSimpleGetIntegerValue(a: CFDictionaryRef, b: i32) -> i64;
     fn IOReportChannelGetUnitLabel(a: CFDictionaryRef) -> CFStringRef;
-    #[allow(dead_code)]
     fn IOReportStateGetCount(a: CFDictionaryRef) -> i32;
-    #[allow(dead_code)]
     fn IOReportStateGetNameForIndex(a: CFDictionaryRef, b: i32) -> CFStringRef;
-    #[allow(dead_code)]
     fn IOReportStateGetResidency(a: CFDictionaryRef, b: i32) -> i64;
 }
 
+// IOReportFormat values (IOKernelReportStructs.h)
+const IOREPORT_FORMAT_SIMPLE: u8 = 1;
+const IOREPORT_FORMAT_STATE: u8 = 2;
+
 // IOReport utility functions
 
 // Get channel group name
@@ -121,24 +128,47 @@ fn get_unit_label(item: CFDictionaryRef) -> String {
     }
 }
 
-// Convert energy value to watts based on unit
-fn energy_to_watts(
-    item: CFDictionaryRef,
-    unit: &str,
-    duration_ms: u64,
-) -> Result<f32, Box<dyn std::error::Error>> {
-    let raw_value = unsafe { IOReportSimpleGetIntegerValue(item, 0) } as f32;
-    let time_factor = duration_ms as f32 / 1000.0; // Convert ms to seconds
-    let value_per_second = raw_value / time_factor;
-
-    let watts = match unit {
-        "mJ" => value_per_second / 1000.0, // millijoules to watts
-        "uJ" | "μJ" => value_per_second / 1_000_000.0, // microjoules to watts
-        "nJ" => value_per_second / 1_000_000_000.0, // nanojoules to watts
-        _ => return Err(format!("Unknown energy unit: {}", unit).into()),
+// Get the state names and residencies of a state channel
+fn get_states(item: CFDictionaryRef) -> Vec<ChannelState> {
+    let count = unsafe { IOReportStateGetCount(item) };
+    (0..count)
+        .map(|i| ChannelState {
+            name: cf_string_to_rust(unsafe { IOReportStateGetNameForIndex(item, i) }),
+            residency: unsafe { IOReportStateGetResidency(item, i) },
+        })
+        .collect()
+}
+
+/// Copy an IOReport delta into the portable sample model
+pub fn sample_from_cf(data: CFDictionaryRef, duration_ms: u64) -> IOReportSample {
+    let mut sample = IOReportSample {
+        duration_ms,
+        channels: Vec::new(),
+    };
+    let Ok(items) = cf_dict_get_array(data, "IOReportChannels") else {
+        return sample;
     };
 
-    Ok(watts)
+    let count = unsafe { CFArrayGetCount(items) };
+    for i in 0..count {
+        let item = unsafe { CFArrayGetValueAtIndex(items, i) } as CFDictionaryRef;
+        let format = unsafe { IOReportChannelGetFormat(item) };
+        sample.channels.push(IOReportChannel {
+            group: get_channel_group(item),
+            subgroup: get_channel_subgroup(item),
+            name: get_channel_name(item),
+            unit: get_unit_label(item),
+            value: (format == IOREPORT_FORMAT_SIMPLE)
+                .then(|| unsafe { IOReportSimpleGetIntegerValue(item, 0) }),
+            states: if format == IOREPORT_FORMAT_STATE {
+                get_states(item)
+            } else {
+                Vec::new()
+            },
+        });
+    }
+
+    sample
 }
 
 pub struct IOServiceIterator {
@@ -189,6 +219,24 @@ impl Iterator for IOServiceIterator {
     }
 }
 
+// Release a registry entry handed out by IOServiceIterator
+pub fn io_object_release(entry: u32) {
+    unsafe {
+        IOObjectRelease(entry);
+    }
+}
+
+// Get the first child of a registry entry in the IOService plane
+pub fn get_io_child(entry: u32) -> Option<u32> {
+    let plane = CString::new("IOService").ok()?;
+    let mut child = 0;
+    if unsafe { IORegistryEntryGetChildEntry(entry, plane.as_ptr(), &mut child) } != 0 || child == 0
+    {
+        return None;
+    }
+    Some(child)
+}
+
 pub fn get_io_props(entry: u32) -> Result<CFDictionaryRef, Box<dyn std::error::Error>> {
     unsafe {
         let mut props: MaybeUninit<CFMutableDictionaryRef> = MaybeUninit::uninit();
@@ -239,14 +287,14 @@ pub type CpuFrequencyResult =
 // Get GPU frequencies from IORegistry
 pub fn get_gpu_frequencies() -> CpuFrequencyResult {
     let mut gpu_freqs = None;
+    let profile = crate::metrics::chip_profile::active_profile();
 
     // Try to get frequency info from pmgr device
     for (entry, name) in IOServiceIterator::new("AppleARMIODevice")? {
         if name == "pmgr" {
             let props = get_io_props(entry)?;
 
-            // GPU frequencies are in voltage-states9
-            if let Some(freqs) = parse_dvfs_mhz(props, "voltage-states9") {
+            if let Some(freqs) = parse_dvfs_mhz(props, &profile.gpu_dvfs_key) {
                 // Convert to MHz (from Hz)
                 let freqs_mhz: Vec<u32> = freqs.iter().map(|&f| f / 1_000_000).collect();
                 gpu_freqs = Some(freqs_mhz);
@@ -265,6 +313,7 @@ pub fn get_cpu_frequencies() -> CpuFrequencyResult {
     let mut ecpu_freqs = None;
     let mut pcpu_freqs = None;
     let chip_name = None;
+    let profile = crate::metrics::chip_profile::active_profile();
 
     // Intentionally avoid system_profiler by default (performance). chip_name left as None.
 
@@ -272,31 +321,15 @@ pub fn get_cpu_frequencies() -> CpuFrequencyResult {
     for (entry, name) in IOServiceIterator::new("AppleARMIODevice")? {
         if name == "pmgr" {
             let props = get_io_props(entry)?;
+            let cpu_scale = cpu_freq_scale(props, profile);
 
-            // Get raw frequency values to determine scale
-            let mut cpu_scale = 1000 * 1000; // Default to Hz->MHz
-
-            // Check a sample frequency to determine if values are in Hz or KHz
-            if let Some(sample_freqs) = parse_dvfs_mhz(props, "voltage-states1-sram")
-                .or_else(|| parse_dvfs_mhz(props, "voltage-states5-sram"))
-                && let Some(&first_freq) = sample_freqs.first()
-            {
-                // If raw value is > 100 MHz (100_000_000 Hz), it's in Hz
-                // If raw value is < 10 MHz (10_000 KHz), it's in KHz
-                if first_freq > 100_000_000 {
-                    cpu_scale = 1000 * 1000; // Hz to MHz
-                } else if first_freq < 10_000 {
-                    cpu_scale = 1000; // KHz to MHz
-                }
-            }
-
-            // Get efficiency core frequencies (voltage-states1-sram)
-            if let Some(freqs) = parse_dvfs_mhz(props, "voltage-states1-sram") {
+            // Efficiency core frequencies (voltage-states1-sram unless the profile says otherwise)
+            if let Some(freqs) = parse_dvfs_mhz(props, &profile.ecpu_dvfs_key) {
                 ecpu_freqs = Some(freqs.into_iter().map(|f| f / cpu_scale).collect());
             }
 
-            // Get performance core frequencies (voltage-states5-sram)
-            if let Some(freqs) = parse_dvfs_mhz(props, "voltage-states5-sram") {
+            // Performance core frequencies (voltage-states5-sram unless the profile says otherwise)
+            if let Some(freqs) = parse_dvfs_mhz(props, &profile.pcpu_dvfs_key) {
                 pcpu_freqs = Some(freqs.into_iter().map(|f| f / cpu_scale).collect());
             }
 
@@ -313,75 +346,64 @@ pub fn get_cpu_frequencies() -> CpuFrequencyResult {
     Ok((ecpu_freqs, pcpu_freqs, chip_name))
 }
 
-// IOReport channel iterator
-pub struct IOReportIterator {
-    sample: CFDictionaryRef,
-    index: isize,
-    items: CFArrayRef,
-    items_size: isize,
-    duration_ms: u64,
-}
-
-impl IOReportIterator {
-    pub fn new(data: CFDictionaryRef, duration_ms: u64) -> Self {
-        let items = cf_dict_get_array(data, "IOReportChannels").unwrap();
-        let items_size = unsafe { CFArrayGetCount(items) } as isize;
-        Self {
-            sample: data,
-            items,
-            items_size,
-            index: 0,
-            duration_ms,
-        }
+// Per-cluster CPU frequency tables for chips whose profile names separate DVFS keys,
+// (E-clusters, P-clusters); an empty table where a key is missing
+pub fn get_cluster_frequencies()
+-> Result<(Vec<Vec<u32>>, Vec<Vec<u32>>), Box<dyn std::error::Error>> {
+    let profile = crate::metrics::chip_profile::active_profile();
+    if profile.ecpu_cluster_dvfs_keys.is_empty() && profile.pcpu_cluster_dvfs_keys.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
     }
 
-    pub fn duration_ms(&self) -> u64 {
-        self.duration_ms
-    }
-}
+    let mut ecpu = Vec::new();
+    let mut pcpu = Vec::new();
+    for (entry, name) in IOServiceIterator::new("AppleARMIODevice")? {
+        if name == "pmgr" {
+            let props = get_io_props(entry)?;
+            let cpu_scale = cpu_freq_scale(props, profile);
+            let read = |keys: &[String]| -> Vec<Vec<u32>> {
+                keys.iter()
+                    .map(|key| {
+                        parse_dvfs_mhz(props, key)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|f| f / cpu_scale)
+                            .collect()
+                    })
+                    .collect()
+            };
+            ecpu = read(&profile.ecpu_cluster_dvfs_keys);
+            pcpu = read(&profile.pcpu_cluster_dvfs_keys);
 
-impl Drop for IOReportIterator {
-    fn drop(&mut self) {
-        unsafe {
-            CFRelease(self.sample as _);
+            unsafe { CFRelease(props as _) };
+            break;
         }
     }
-}
 
-#[derive(Debug)]
-pub struct IOReportChannel {
-    pub group: String,
-    #[allow(dead_code)]
-    pub subgroup: String,
-    pub channel: String,
-    pub unit: String,
-    pub item: CFDictionaryRef,
+    Ok((ecpu, pcpu))
 }
 
-impl Iterator for IOReportIterator {
-    type Item = IOReportChannel;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.items_size {
-            return None;
+// Divisor turning the raw CPU DVFS values into MHz; older chips report Hz, newer ones KHz
+fn cpu_freq_scale(
+    props: CFDictionaryRef,
+    profile: &crate::metrics::chip_profile::ChipProfile,
+) -> u32 {
+    let mut cpu_scale = 1000 * 1000; // Default to Hz->MHz
+
+    // Check a sample frequency to determine if values are in Hz or KHz
+    if let Some(sample_freqs) = parse_dvfs_mhz(props, &profile.ecpu_dvfs_key)
+        .or_else(|| parse_dvfs_mhz(props, &profile.pcpu_dvfs_key))
+        && let Some(&first_freq) = sample_freqs.first()
+    {
+        // If raw value is > 100 MHz (100_000_000 Hz), it's in Hz
+        // If raw value is < 10 MHz (10_000 KHz), it's in KHz
+        if first_freq > 100_000_000 {
+            cpu_scale = 1000 * 1000; // Hz to MHz
+        } else if first_freq < 10_000 {
+            cpu_scale = 1000; // KHz to MHz
         }
-
-        let item = unsafe { CFArrayGetValueAtIndex(self.items, self.index) } as CFDictionaryRef;
-
-        let group = get_channel_group(item);
-        let subgroup = get_channel_subgroup(item);
-        let channel = get_channel_name(item);
-        let unit = get_unit_label(item);
-
-        self.index += 1;
-        Some(IOReportChannel {
-            group,
-            subgroup,
-            channel,
-            unit,
-            item,
-        })
     }
+    cpu_scale
 }
 
 // Main IOReport interface
@@ -392,7 +414,7 @@ pub struct IOReport {
 
 impl IOReport {
     // Create IOReport instance for specific channel groups
-    pub fn new(groups: Vec<(&str, Option<&str>)>) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(groups: Vec<(&str, Option<&str>)>) -> Result<Self, AtopError> {
         let channels = if groups.is_empty() {
             // Get all channels if no specific groups requested
             unsafe {
@@ -454,7 +476,10 @@ impl IOReport {
 
         // Verify we got channels
         if cf_dict_get_array(channels, "IOReportChannels").is_err() {
-            return Err("Failed to get IOReport channels".into());
+            return Err(AtopError::unavailable(
+                Subsystem::Power,
+                "no IOReport channels in the requested groups",
+            ));
         }
 
         // Create subscription
@@ -464,7 +489,10 @@ impl IOReport {
         };
 
         if subscription.is_null() {
-            return Err("Failed to create IOReport subscription".into());
+            return Err(AtopError::unavailable(
+                Subsystem::Power,
+                "IOReport subscription failed",
+            ));
         }
 
         Ok(Self {
@@ -473,11 +501,8 @@ impl IOReport {
         })
     }
 
-    // Take a power measurement sample over a duration
-    pub fn sample_power(
-        &self,
-        duration_ms: u64,
-    ) -> Result<IOReportIterator, Box<dyn std::error::Error>> {
+    // Take a sample of the subscribed channels over a duration
+    pub fn sample_power(&self, duration_ms: u64) -> Result<IOReportSample, AtopError> {
         unsafe {
             // Take first sample
             let sample1 = IOReportCreateSamples(self.subscription, self.channels, null());
@@ -496,7 +521,9 @@ impl IOReport {
             CFRelease(sample1 as _);
             CFRelease(sample2 as _);
 
-            Ok(IOReportIterator::new(delta, elapsed_ms))
+            let sample = sample_from_cf(delta, elapsed_ms);
+            CFRelease(delta as _);
+            Ok(sample)
         }
     }
 }
@@ -510,69 +537,29 @@ impl Drop for IOReport {
     }
 }
 
-// Power metrics structure
-#[derive(Debug, Default, Serialize, Clone)]
-pub struct PowerMetrics {
-    pub cpu_power: f32,     // Watts
-    pub gpu_power: f32,     // Watts
-    pub ane_power: f32,     // Watts (Apple Neural Engine)
-    pub ram_power: f32,     // Watts
-    pub gpu_ram_power: f32, // Watts
-    pub all_power: f32,     // Combined CPU+GPU+ANE
-    pub sys_power: f32,     // Total system power
-}
-
 // Collect power metrics from an existing IOReport instance
 pub fn get_power_metrics_from_sample(
     ioreport: &IOReport,
     interval_ms: u64,
-) -> Result<PowerMetrics, Box<dyn std::error::Error>> {
-    // Take a sample with specified interval to get power readings
+) -> Result<PowerMetrics, AtopError> {
+    // Take a sample with specified interval to get power readings; the parser uses the
+    // measured duration since IOReport timing may drift slightly
     let sample = ioreport.sample_power(interval_ms)?;
-    let actual_duration_ms = sample.duration_ms();
-
-    let mut metrics = PowerMetrics::default();
-
-    // Process each channel in the sample
-    for channel in sample {
-        if channel.group == "Energy Model" {
-            // Use measured duration to convert energy to power; IOReport timing may drift slightly.
-            let power_result = energy_to_watts(channel.item, &channel.unit, actual_duration_ms);
-
-            match power_result {
-                Ok(watts) => {
-                    match channel.channel.as_str() {
-                        "GPU Energy" => metrics.gpu_power += watts,
-                        // Handle different CPU energy patterns for different chip types
-                        c if c.ends_with("CPU Energy") => metrics.cpu_power += watts,
-                        // Handle ANE (Apple Neural Engine) patterns
-                        c if c.starts_with("ANE") => metrics.ane_power += watts,
-                        // Handle memory power patterns
-                        c if c.starts_with("DRAM") => metrics.ram_power += watts,
-                        c if c.starts_with("GPU SRAM") => metrics.gpu_ram_power += watts,
-                        _ => {}
-                    }
-                }
-                Err(_) => {
-                    // Skip channels with unknown units or conversion errors
-                }
-            }
-        }
-    }
-
-    // Calculate combined power
-    metrics.all_power = metrics.cpu_power + metrics.gpu_power + metrics.ane_power;
-
-    // Use calculated total for system power
-    metrics.sys_power = metrics.all_power;
-
-    Ok(metrics)
+    ioreport_sample::parse_power(&sample, crate::metrics::chip_profile::active_profile())
 }
 
-// Legacy function - creates new IOReport instance each time (can cause memory leak if called repeatedly)
-pub fn get_power_metrics_with_interval(
-    interval_ms: u64,
-) -> Result<PowerMetrics, Box<dyn std::error::Error>> {
-    let ioreport = IOReport::new(vec![("Energy Model", None)])?;
-    get_power_metrics_from_sample(&ioreport, interval_ms)
+// Subscribe to every channel and capture one delta, for bug reports on unsupported chips
+pub fn capture_channel_dump(interval_ms: u64) -> Result<ChannelDump, Box<dyn std::error::Error>> {
+    let ioreport = IOReport::new(vec![])?;
+    let sample = ioreport.sample_power(interval_ms)?;
+    let chip = crate::metrics::get_cpu_metrics()
+        .map(|cpu| cpu.cpu_brand)
+        .unwrap_or_default();
+
+    Ok(ChannelDump {
+        atop_version: env!("CARGO_PKG_VERSION").to_string(),
+        chip,
+        freqs: crate::metrics::ioreport_perf::freq_tables(),
+        sample,
+    })
 }
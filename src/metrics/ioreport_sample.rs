@@ -0,0 +1,515 @@
+// Plain-Rust model of an IOReport delta and the parsers that turn it into metrics.
+// The macOS collectors convert from CF first, so the math runs (and is tested) anywhere.
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::chip_profile::{ChipProfile, ChipProfiles, matches_any};
+use crate::metrics::error::{AtopError, Subsystem};
+use crate::metrics::performance::{ClusterSample, PerformanceSample};
+use crate::metrics::power::PowerMetrics;
+
+/// One performance or power state of a state channel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChannelState {
+    pub name: String,
+    pub residency: i64, // time spent in the state during the delta, in IOReport ticks
+}
+
+/// One channel of an IOReport delta
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IOReportChannel {
+    pub group: String,
+    pub subgroup: String,
+    pub name: String,
+    pub unit: String, // trimmed unit label, e.g. "mJ"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<i64>, // simple (integer) channels only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub states: Vec<ChannelState>, // state channels only, in IOReport order
+}
+
+/// Difference between two IOReport samples
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IOReportSample {
+    pub duration_ms: u64, // measured time between the two samples
+    pub channels: Vec<IOReportChannel>,
+}
+
+/// DVFS tables the residency states map onto, in MHz
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FreqTables {
+    pub ecpu_mhz: Vec<u32>,
+    pub pcpu_mhz: Vec<u32>,
+    pub gpu_mhz: Vec<u32>, // first entry is the idle state
+    // Separate tables per cluster where the chip profile names them; empty entries use the shared table
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ecpu_cluster_mhz: Vec<Vec<u32>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pcpu_cluster_mhz: Vec<Vec<u32>>,
+}
+
+/// Every IOReport channel from one delta, with what's needed to replay it elsewhere
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChannelDump {
+    pub atop_version: String,
+    pub chip: String, // CPU brand string of the machine that captured the dump
+    pub freqs: FreqTables,
+    pub sample: IOReportSample,
+}
+
+impl ChannelDump {
+    pub fn load(path: &str) -> Result<Self, AtopError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AtopError::unavailable(Subsystem::Power, format!("Failed to read {}: {}", path, e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            AtopError::read(
+                Subsystem::Power,
+                format!("Invalid channel dump {}: {}", path, e),
+            )
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), AtopError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| AtopError::read(Subsystem::Power, e))?;
+        std::fs::write(path, json + "\n").map_err(|e| {
+            AtopError::unavailable(Subsystem::Power, format!("Failed to write {}: {}", path, e))
+        })
+    }
+
+    /// Run the captured delta through the power and performance parsers, using the profile
+    /// `profiles` selects for the chip that captured it
+    pub fn replay_with(
+        &self,
+        profiles: &ChipProfiles,
+    ) -> Result<(ChipProfile, PowerMetrics, PerformanceSample), AtopError> {
+        let profile = profiles.select(&self.chip);
+        let power = parse_power(&self.sample, &profile)?;
+        let performance = parse_performance(&self.sample, &self.freqs, &profile);
+        Ok((profile, power, performance))
+    }
+}
+
+// Convert an energy counter to watts based on its unit
+fn energy_to_watts(
+    value: i64,
+    unit: &str,
+    duration_ms: u64,
+) -> Result<f32, Box<dyn std::error::Error>> {
+    let time_factor = duration_ms as f32 / 1000.0; // Convert ms to seconds
+    let value_per_second = value as f32 / time_factor;
+
+    let watts = match unit {
+        "mJ" => value_per_second / 1000.0, // millijoules to watts
+        "uJ" | "μJ" => value_per_second / 1_000_000.0, // microjoules to watts
+        "nJ" => value_per_second / 1_000_000_000.0, // nanojoules to watts
+        _ => return Err(format!("unknown energy unit \"{}\"", unit).into()),
+    };
+
+    Ok(watts)
+}
+
+/// Power per component from the "Energy Model" channels named in the profile; a channel the
+/// profile uses but whose unit we can't convert is an error rather than a silent 0 W
+pub fn parse_power(
+    sample: &IOReportSample,
+    profile: &ChipProfile,
+) -> Result<PowerMetrics, AtopError> {
+    let mut metrics = PowerMetrics::default();
+
+    for channel in &sample.channels {
+        if channel.group != "Energy Model" {
+            continue;
+        }
+        let Some(value) = channel.value else {
+            continue;
+        };
+
+        // Only channels the profile uses have to convert
+        let name = channel.name.as_str();
+        let used = [
+            &profile.gpu_energy,
+            &profile.cpu_energy,
+            &profile.ane_energy,
+            &profile.dram_energy,
+            &profile.gpu_sram_energy,
+        ];
+        if !used.iter().any(|patterns| matches_any(patterns, name)) {
+            continue;
+        }
+        let watts = energy_to_watts(value, &channel.unit, sample.duration_ms)
+            .map_err(|e| AtopError::read(Subsystem::Power, format!("{} on {}", e, name)))?;
+
+        if matches_any(&profile.gpu_energy, name) {
+            metrics.gpu_power += watts;
+        } else if matches_any(&profile.cpu_energy, name) {
+            metrics.cpu_power += watts;
+            if matches_any(&profile.ecpu_energy, name) {
+                metrics.ecpu_power += watts;
+            } else if matches_any(&profile.pcpu_energy, name) {
+                metrics.pcpu_power += watts;
+            }
+        } else if matches_any(&profile.ane_energy, name) {
+            metrics.ane_power += watts;
+        } else if matches_any(&profile.dram_energy, name) {
+            metrics.ram_power += watts;
+        } else {
+            metrics.gpu_ram_power += watts;
+        }
+    }
+
+    // Calculate combined power
+    metrics.all_power = metrics.cpu_power + metrics.gpu_power + metrics.ane_power;
+
+    // Use calculated total for system power
+    metrics.sys_power = metrics.all_power;
+
+    Ok(metrics)
+}
+
+/// Calculate frequency, utilization and active residency ratio from performance state residencies
+pub fn calc_freq(states: &[ChannelState], freqs: &[u32]) -> (u32, f32, f32) {
+    // Find the first active state (skip IDLE/DOWN/OFF states)
+    let offset = states
+        .iter()
+        .position(|s| s.name != "IDLE" && s.name != "DOWN" && s.name != "OFF")
+        .unwrap_or(0);
+
+    // Calculate total active time and overall time
+    let usage = states
+        .iter()
+        .skip(offset)
+        .map(|s| s.residency as f64)
+        .sum::<f64>();
+    let total = states.iter().map(|s| s.residency as f64).sum::<f64>();
+
+    if usage == 0.0 || total == 0.0 || freqs.is_empty() {
+        return (0, 0.0, 0.0);
+    }
+
+    // Calculate weighted average frequency
+    let mut avg_freq = 0f64;
+    for i in 0..freqs.len().min(states.len() - offset) {
+        let percent = states[i + offset].residency as f64 / usage;
+        avg_freq += percent * freqs[i] as f64;
+    }
+
+    // Calculate utilization percentage
+    let usage_ratio = usage / total;
+    let min_freq = *freqs.first().unwrap() as f64;
+    let max_freq = *freqs.last().unwrap() as f64;
+    let from_max = (avg_freq.max(min_freq) * usage_ratio) / max_freq;
+
+    (avg_freq as u32, from_max as f32, usage_ratio as f32)
+}
+
+/// CPU cluster and GPU frequency and utilization from the performance state channels
+pub fn parse_performance(
+    sample: &IOReportSample,
+    freqs: &FreqTables,
+    profile: &ChipProfile,
+) -> PerformanceSample {
+    let mut perf = PerformanceSample::default();
+    let mut ecpu_cores = Vec::new();
+    let mut pcpu_cores = Vec::new();
+
+    for channel in &sample.channels {
+        if channel.group == "CPU Stats" && channel.subgroup == "CPU Core Performance States" {
+            if matches_any(&profile.ecpu_states, &channel.name) {
+                ecpu_cores.push(&channel.states);
+            } else if matches_any(&profile.pcpu_states, &channel.name) {
+                pcpu_cores.push(&channel.states);
+            }
+        } else if channel.group == "GPU Stats"
+            && channel.subgroup == "GPU Performance States"
+            && matches_any(&profile.gpu_states, &channel.name)
+            && !freqs.gpu_mhz.is_empty()
+        {
+            // Skip the first frequency (idle state)
+            let (freq, util, _) = calc_freq(&channel.states, &freqs.gpu_mhz[1..]);
+            perf.gpu_usage = (freq, util);
+        }
+    }
+
+    // Core channels come in core order, so each cluster takes the next `cores` of them
+    let mut ecpu_usages = Vec::new();
+    let mut pcpu_usages = Vec::new();
+    let clusters = profile.clusters(ecpu_cores.len() as u32, pcpu_cores.len() as u32, freqs);
+    let (mut ecpu_cores, mut pcpu_cores) = (ecpu_cores.into_iter(), pcpu_cores.into_iter());
+    for cluster in clusters {
+        let (cores, usages) = if cluster.efficiency {
+            (&mut ecpu_cores, &mut ecpu_usages)
+        } else {
+            (&mut pcpu_cores, &mut pcpu_usages)
+        };
+        let cluster_usages: Vec<_> = cores
+            .take(cluster.cores as usize)
+            .map(|states| calc_freq(states, &cluster.freqs_mhz))
+            .collect();
+        if let Some((freq_mhz, utilization, active)) = average(&cluster_usages) {
+            perf.clusters.push(ClusterSample {
+                id: cluster.id,
+                freq_mhz,
+                utilization,
+                active,
+            });
+        }
+        usages.extend(cluster_usages);
+    }
+
+    // Average the per-core measurements
+    if let Some((freq, util, active)) = average(&ecpu_usages) {
+        perf.ecpu_usage = (freq, util);
+        perf.ecpu_active = active;
+    }
+    if let Some((freq, util, active)) = average(&pcpu_usages) {
+        perf.pcpu_usage = (freq, util);
+        perf.pcpu_active = active;
+    }
+
+    perf
+}
+
+fn average(usages: &[(u32, f32, f32)]) -> Option<(u32, f32, f32)> {
+    if usages.is_empty() {
+        return None;
+    }
+    let n = usages.len() as f32;
+    let freq = usages.iter().map(|x| x.0 as f32).sum::<f32>() / n;
+    let util = usages.iter().map(|x| x.1).sum::<f32>() / n;
+    let active = usages.iter().map(|x| x.2).sum::<f32>() / n;
+    Some((freq as u32, util, active))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states(residencies: &[(&str, i64)]) -> Vec<ChannelState> {
+        residencies
+            .iter()
+            .map(|&(name, residency)| ChannelState {
+                name: name.to_string(),
+                residency,
+            })
+            .collect()
+    }
+
+    fn energy(name: &str, unit: &str, value: i64) -> IOReportChannel {
+        IOReportChannel {
+            group: "Energy Model".to_string(),
+            name: name.to_string(),
+            unit: unit.to_string(),
+            value: Some(value),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn converts_energy_to_watts() {
+        let sample = IOReportSample {
+            duration_ms: 500,
+            channels: vec![
+                energy("EACC_CPU Energy", "mJ", 250),
+                energy("PACC0_CPU Energy", "mJ", 1000),
+                energy("GPU Energy", "uJ", 2_000_000),
+                energy("ANE0", "nJ", 500_000_000),
+                energy("DRAM0", "mJ", 300),
+                energy("GPU SRAM0", "mJ", 50),
+                energy("ISP Energy", "furlongs", 1000),
+            ],
+        };
+
+        // Channels the profile doesn't use are skipped whatever their unit
+        let power = parse_power(&sample, &ChipProfile::default()).unwrap();
+        assert_eq!(power.ecpu_power, 0.5);
+        assert_eq!(power.pcpu_power, 2.0);
+        assert_eq!(power.cpu_power, 2.5);
+        assert_eq!(power.gpu_power, 4.0);
+        assert_eq!(power.ane_power, 1.0);
+        assert_eq!(power.ram_power, 0.6);
+        assert_eq!(power.gpu_ram_power, 0.1);
+        assert_eq!(power.all_power, 7.5);
+        assert_eq!(power.sys_power, 7.5);
+    }
+
+    #[test]
+    fn rejects_unknown_units_on_used_channels() {
+        let sample = IOReportSample {
+            duration_ms: 1000,
+            channels: vec![energy("CPU Energy", "furlongs", 1000)],
+        };
+        let err = parse_power(&sample, &ChipProfile::default()).unwrap_err();
+        assert_eq!(err.subsystem(), Subsystem::Power);
+        assert_eq!(
+            err.to_string(),
+            "power: unknown energy unit \"furlongs\" on CPU Energy"
+        );
+    }
+
+    #[test]
+    fn weights_frequency_by_residency() {
+        // Half the time idle, the rest split evenly between 1000 and 3000 MHz
+        let residencies = states(&[("IDLE", 200), ("V0P1", 100), ("V1P2", 100)]);
+        let (freq, util, active) = calc_freq(&residencies, &[1000, 3000]);
+        assert_eq!(freq, 2000);
+        assert_eq!(active, 0.5);
+        assert!((util - 2000.0 * 0.5 / 3000.0).abs() < 1e-6);
+
+        assert_eq!(
+            calc_freq(&states(&[("IDLE", 10), ("V0P1", 0)]), &[1000]),
+            (0, 0.0, 0.0)
+        );
+        assert_eq!(calc_freq(&residencies, &[]), (0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn parses_captured_performance_states() {
+        let sample: IOReportSample = serde_json::from_str(
+            r#"{
+                "duration_ms": 1000,
+                "channels": [
+                    {"group": "CPU Stats", "subgroup": "CPU Core Performance States",
+                     "name": "ECPU0", "unit": "",
+                     "states": [{"name": "IDLE", "residency": 50}, {"name": "V0P1", "residency": 50}]},
+                    {"group": "CPU Stats", "subgroup": "CPU Core Performance States",
+                     "name": "ECPU1", "unit": "",
+                     "states": [{"name": "IDLE", "residency": 100}, {"name": "V0P1", "residency": 0}]},
+                    {"group": "CPU Stats", "subgroup": "CPU Core Performance States",
+                     "name": "PCPU0", "unit": "",
+                     "states": [{"name": "DOWN", "residency": 0}, {"name": "V0P1", "residency": 0},
+                                {"name": "V1P2", "residency": 100}]},
+                    {"group": "GPU Stats", "subgroup": "GPU Performance States",
+                     "name": "GPUPH", "unit": "",
+                     "states": [{"name": "OFF", "residency": 75}, {"name": "P1", "residency": 25}]},
+                    {"group": "Energy Model", "subgroup": "", "name": "GPU Energy",
+                     "unit": "mJ", "value": 1500}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let freqs = FreqTables {
+            ecpu_mhz: vec![600, 2064],
+            pcpu_mhz: vec![600, 3228],
+            gpu_mhz: vec![0, 400, 1296],
+            ..Default::default()
+        };
+
+        let perf = parse_performance(&sample, &freqs, &ChipProfile::default());
+        // ECPU1 was fully idle, so it counts as 0 MHz in the cluster average
+        assert_eq!(perf.ecpu_usage.0, 300);
+        assert_eq!(perf.ecpu_active, 0.25);
+        assert_eq!(perf.pcpu_usage, (3228, 1.0));
+        assert_eq!(perf.pcpu_active, 1.0);
+        assert_eq!(perf.gpu_usage.0, 400);
+
+        assert_eq!(
+            parse_power(&sample, &ChipProfile::default())
+                .unwrap()
+                .gpu_power,
+            1.5
+        );
+    }
+
+    #[test]
+    fn splits_core_channels_into_clusters() {
+        let core = |name: &str, top: i64| IOReportChannel {
+            group: "CPU Stats".to_string(),
+            subgroup: "CPU Core Performance States".to_string(),
+            name: name.to_string(),
+            states: states(&[("IDLE", 0), ("V0P1", 100 - top), ("V1P2", top)]),
+            ..Default::default()
+        };
+        let sample = IOReportSample {
+            duration_ms: 1000,
+            channels: vec![
+                core("ECPU0", 0),
+                core("PCPU0", 100),
+                core("PCPU1", 100),
+                core("PCPU2", 0),
+                core("PCPU3", 0),
+            ],
+        };
+        let freqs = FreqTables {
+            ecpu_mhz: vec![600, 2064],
+            pcpu_mhz: vec![600, 3228],
+            pcpu_cluster_mhz: vec![vec![], vec![700, 3000]],
+            ..Default::default()
+        };
+        let profile = ChipProfile {
+            pcpu_clusters: 2,
+            ..Default::default()
+        };
+
+        let perf = parse_performance(&sample, &freqs, &profile);
+        let clusters: Vec<(&str, u32)> = perf
+            .clusters
+            .iter()
+            .map(|c| (c.id.as_str(), c.freq_mhz))
+            .collect();
+        assert_eq!(clusters, [("E0", 600), ("P0", 3228), ("P1", 700)]);
+        assert_eq!(perf.pcpu_usage.0, (3228 * 2 + 700 * 2) / 4);
+    }
+
+    #[test]
+    fn replays_a_channel_dump() {
+        let dump = ChannelDump {
+            atop_version: "0.1.0".to_string(),
+            chip: "Apple M1".to_string(),
+            freqs: FreqTables {
+                ecpu_mhz: vec![600, 2064],
+                pcpu_mhz: vec![600, 3204],
+                gpu_mhz: vec![0, 396, 1278],
+                ..Default::default()
+            },
+            sample: IOReportSample {
+                duration_ms: 1000,
+                channels: vec![
+                    energy("ECPU Energy", "mJ", 100),
+                    energy("PCPU Energy", "mJ", 900),
+                    IOReportChannel {
+                        group: "CPU Stats".to_string(),
+                        subgroup: "CPU Core Performance States".to_string(),
+                        name: "PCPU2".to_string(),
+                        states: states(&[("IDLE", 0), ("V0P1", 1), ("V1P2", 3)]),
+                        ..Default::default()
+                    },
+                ],
+            },
+        };
+
+        let json = serde_json::to_string_pretty(&dump).unwrap();
+        let (profile, power, perf) = serde_json::from_str::<ChannelDump>(&json)
+            .unwrap()
+            .replay_with(&ChipProfiles::builtin())
+            .unwrap();
+        assert_eq!(profile.name, "M1");
+        assert_eq!(power.cpu_power, 1.0);
+        assert_eq!(power.ecpu_power, 0.1);
+        assert_eq!(perf.pcpu_usage.0, (600.0 * 0.25 + 3204.0 * 0.75) as u32);
+        assert_eq!(perf.pcpu_active, 1.0);
+        assert_eq!(perf.ecpu_usage, (0, 0.0));
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let sample = IOReportSample {
+            duration_ms: 250,
+            channels: vec![
+                energy("DRAM0", "mJ", 12),
+                IOReportChannel {
+                    group: "GPU Stats".to_string(),
+                    subgroup: "GPU Performance States".to_string(),
+                    name: "GPUPH".to_string(),
+                    states: states(&[("OFF", 3), ("P1", 4)]),
+                    ..Default::default()
+                },
+            ],
+        };
+        let json = serde_json::to_string(&sample).unwrap();
+        assert_eq!(
+            serde_json::from_str::<IOReportSample>(&json).unwrap(),
+            sample
+        );
+    }
+}
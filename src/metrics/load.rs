@@ -0,0 +1,187 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use serde::Serialize;
+use std::error::Error;
+use std::time::Instant;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct LoadMetrics {
+    pub load_1: f32,
+    pub load_5: f32,
+    pub load_15: f32,
+    pub context_switches_per_sec: Option<f32>, // Linux only
+    pub interrupts_per_sec: Option<f32>,       // Linux only
+    pub forks_per_sec: Option<f32>,            // Linux only
+    pub running_tasks: Option<u32>,            // runnable threads right now, Linux only
+    pub blocked_tasks: Option<u32>,            // waiting on I/O, Linux only
+}
+
+// Load averages, cumulative scheduler counters and the instantaneous task counts
+#[derive(Debug, Default, Clone)]
+struct SchedCounters {
+    loads: [f32; 3],
+    context_switches: Option<u64>,
+    interrupts: Option<u64>,
+    forks: Option<u64>,
+    running_tasks: Option<u32>,
+    blocked_tasks: Option<u32>,
+}
+
+/// Load averages with context switch, interrupt and fork rates between samples
+pub struct LoadSampler {
+    prev: Option<SchedCounters>,
+    prev_time: Instant,
+}
+
+impl LoadSampler {
+    pub fn new() -> Self {
+        Self {
+            prev: read_counters().ok(),
+            prev_time: Instant::now(),
+        }
+    }
+
+    /// Rates are `None` until two samples have been read
+    pub fn sample(&mut self) -> Result<LoadMetrics, AtopError> {
+        let cur = read_counters().subsystem(Subsystem::Load)?;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.prev_time).as_secs_f32();
+
+        let prev = self.prev.as_ref();
+        let rate = |cur: Option<u64>, prev: Option<u64>| match (cur, prev) {
+            (Some(cur), Some(prev)) if elapsed > 0.0 => {
+                Some(cur.saturating_sub(prev) as f32 / elapsed)
+            }
+            _ => None,
+        };
+        let metrics = LoadMetrics {
+            load_1: cur.loads[0],
+            load_5: cur.loads[1],
+            load_15: cur.loads[2],
+            context_switches_per_sec: rate(
+                cur.context_switches,
+                prev.and_then(|p| p.context_switches),
+            ),
+            interrupts_per_sec: rate(cur.interrupts, prev.and_then(|p| p.interrupts)),
+            forks_per_sec: rate(cur.forks, prev.and_then(|p| p.forks)),
+            running_tasks: cur.running_tasks,
+            blocked_tasks: cur.blocked_tasks,
+        };
+
+        self.prev = Some(cur);
+        self.prev_time = now;
+
+        Ok(metrics)
+    }
+}
+
+impl Default for LoadSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_counters() -> Result<SchedCounters, Box<dyn Error>> {
+    let mut counters = parse_stat(&std::fs::read_to_string("/proc/stat")?);
+    counters.loads = parse_loadavg(&std::fs::read_to_string("/proc/loadavg")?)
+        .ok_or("Failed to parse /proc/loadavg")?;
+    Ok(counters)
+}
+
+// "0.52 0.58 0.59 2/1123 45678": the 1, 5 and 15 minute averages come first
+#[cfg(target_os = "linux")]
+fn parse_loadavg(content: &str) -> Option<[f32; 3]> {
+    let mut fields = content.split_whitespace().map(|f| f.parse::<f32>().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
+#[cfg(target_os = "linux")]
+fn parse_stat(content: &str) -> SchedCounters {
+    let mut counters = SchedCounters::default();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let key = fields.next();
+        // "intr" is followed by per-IRQ counts; the first value is the total
+        let value = fields.next().and_then(|v| v.parse::<u64>().ok());
+        match key {
+            Some("ctxt") => counters.context_switches = value,
+            Some("intr") => counters.interrupts = value,
+            Some("processes") => counters.forks = value,
+            Some("procs_running") => counters.running_tasks = value.map(|v| v as u32),
+            Some("procs_blocked") => counters.blocked_tasks = value.map(|v| v as u32),
+            _ => {}
+        }
+    }
+    counters
+}
+
+// host_statistics64 and host_processor_info carry no context switch, interrupt, fork or
+// run-queue counters, so macOS reports the load averages only
+#[cfg(target_os = "macos")]
+fn read_counters() -> Result<SchedCounters, Box<dyn Error>> {
+    let mut loads = [0f64; 3];
+    if unsafe { libc::getloadavg(loads.as_mut_ptr(), 3) } != 3 {
+        return Err("getloadavg failed".into());
+    }
+    Ok(SchedCounters {
+        loads: loads.map(|l| l as f32),
+        ..Default::default()
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_stat_counters() {
+        let counters = parse_stat(
+            "\
+cpu  4705 356 584 3699 23 23 0 0 0 0
+cpu0 1393280 32966 572056 13343292 6130 0 17875 0 0 0
+intr 114930548 113199788 3 0 5 263 0 4 [...]
+ctxt 1990473
+btime 1062191376
+processes 2915
+procs_running 3
+procs_blocked 1
+softirq 2000 0 1000 0 0 0 0 0 1000 0 0
+",
+        );
+        assert_eq!(counters.context_switches, Some(1990473));
+        assert_eq!(counters.interrupts, Some(114930548));
+        assert_eq!(counters.forks, Some(2915));
+        assert_eq!(counters.running_tasks, Some(3));
+        assert_eq!(counters.blocked_tasks, Some(1));
+
+        let empty = parse_stat("cpu  1 2 3 4\n");
+        assert_eq!((empty.context_switches, empty.running_tasks), (None, None));
+    }
+
+    #[test]
+    fn parses_proc_loadavg() {
+        assert_eq!(
+            parse_loadavg("0.52 1.25 10.00 2/1123 45678\n"),
+            Some([0.52, 1.25, 10.0])
+        );
+        assert_eq!(parse_loadavg("0.52 1.25\n"), None);
+        assert_eq!(parse_loadavg("a b c 1/2 3\n"), None);
+    }
+
+    #[test]
+    fn rates_need_two_samples() {
+        let mut sampler = LoadSampler {
+            prev: None,
+            prev_time: Instant::now(),
+        };
+        let first = sampler.sample().unwrap();
+        assert!(first.context_switches_per_sec.is_none());
+        assert!(first.interrupts_per_sec.is_none() && first.forks_per_sec.is_none());
+        assert!(first.running_tasks.is_some());
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let second = sampler.sample().unwrap();
+        assert!(second.context_switches_per_sec.is_some_and(|r| r > 0.0));
+        assert!(second.forks_per_sec.is_some());
+    }
+}
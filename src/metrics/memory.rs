@@ -1,6 +1,11 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
 use serde::Serialize;
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
 use std::error::Error;
+#[cfg(target_os = "macos")]
 use std::mem;
+use std::time::Instant;
 
 #[derive(Debug, Default, Serialize, Clone)]
 pub struct MemoryMetrics {
@@ -9,22 +14,131 @@ pub struct MemoryMetrics {
     pub ram_usage: u64,  // bytes
     pub swap_total: u64, // bytes
     pub swap_usage: u64, // bytes
+    pub breakdown: MemoryBreakdown,
+    pub activity: MemoryActivity,
+    pub pressure: MemoryPressure,
 }
 
-pub fn get_memory_metrics() -> Result<MemoryMetrics, Box<dyn Error>> {
-    let (ram_usage, ram_total) = get_ram_info()?;
+// RAM split by page state, all in bytes
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct MemoryBreakdown {
+    pub active: u64,
+    pub inactive: u64,
+    pub wired: u64,
+    pub speculative: u64,
+    pub compressed: u64, // memory occupied by the compressor
+    pub purgeable: u64,
+    pub external: u64, // file-backed pages
+    pub free: u64,
+}
+
+// Paging activity per second, in pages (only filled in by MemorySampler)
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct MemoryActivity {
+    pub pageins_per_sec: f32,
+    pub pageouts_per_sec: f32,
+    pub swapins_per_sec: f32,
+    pub swapouts_per_sec: f32,
+    pub compressions_per_sec: f32,
+    pub decompressions_per_sec: f32,
+}
+
+#[derive(Debug, Default, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryPressure {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+// Cumulative paging counters (pages)
+#[derive(Debug, Default, Clone)]
+struct MemoryCounters {
+    pageins: u64,
+    pageouts: u64,
+    swapins: u64,
+    swapouts: u64,
+    compressions: u64,
+    decompressions: u64,
+}
+
+struct RamInfo {
+    total: u64,
+    usage: u64,
+    breakdown: MemoryBreakdown,
+    counters: MemoryCounters,
+}
+
+fn read_memory_metrics() -> Result<(MemoryMetrics, MemoryCounters), Box<dyn Error>> {
+    let ram = get_ram_info()?;
     let (swap_usage, swap_total) = get_swap_info()?;
+    let pressure = get_memory_pressure(&ram);
 
-    Ok(MemoryMetrics {
-        total: ram_total + swap_total,
-        ram_total,
-        ram_usage,
-        swap_total,
-        swap_usage,
-    })
+    Ok((
+        MemoryMetrics {
+            total: ram.total + swap_total,
+            ram_total: ram.total,
+            ram_usage: ram.usage,
+            swap_total,
+            swap_usage,
+            breakdown: ram.breakdown,
+            activity: MemoryActivity::default(),
+            pressure,
+        },
+        ram.counters,
+    ))
+}
+
+/// Memory metrics with paging activity rates computed between samples
+pub struct MemorySampler {
+    prev: Option<MemoryCounters>,
+    prev_time: Instant,
+}
+
+impl MemorySampler {
+    pub fn new() -> Self {
+        Self {
+            prev: read_memory_metrics().ok().map(|(_, counters)| counters),
+            prev_time: Instant::now(),
+        }
+    }
+
+    /// Current memory state plus activity since the previous call
+    pub fn sample(&mut self) -> Result<MemoryMetrics, AtopError> {
+        let (mut metrics, counters) = read_memory_metrics().subsystem(Subsystem::Memory)?;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.prev_time).as_secs_f64();
+
+        if let Some(ref prev) = self.prev
+            && elapsed > 0.0
+        {
+            let rate = |cur: u64, prev: u64| (cur.saturating_sub(prev) as f64 / elapsed) as f32;
+            metrics.activity = MemoryActivity {
+                pageins_per_sec: rate(counters.pageins, prev.pageins),
+                pageouts_per_sec: rate(counters.pageouts, prev.pageouts),
+                swapins_per_sec: rate(counters.swapins, prev.swapins),
+                swapouts_per_sec: rate(counters.swapouts, prev.swapouts),
+                compressions_per_sec: rate(counters.compressions, prev.compressions),
+                decompressions_per_sec: rate(counters.decompressions, prev.decompressions),
+            };
+        }
+
+        self.prev = Some(counters);
+        self.prev_time = now;
+
+        Ok(metrics)
+    }
 }
 
-fn get_ram_info() -> Result<(u64, u64), Box<dyn Error>> {
+impl Default for MemorySampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn get_ram_info() -> Result<RamInfo, Box<dyn Error>> {
     let mut total = 0u64;
 
     unsafe {
@@ -44,7 +158,7 @@ fn get_ram_info() -> Result<(u64, u64), Box<dyn Error>> {
         }
     }
 
-    let usage = unsafe {
+    unsafe {
         let mut count: u32 = libc::HOST_VM_INFO64_COUNT as _;
         let mut stats = mem::zeroed::<libc::vm_statistics64>();
 
@@ -61,19 +175,45 @@ fn get_ram_info() -> Result<(u64, u64), Box<dyn Error>> {
 
         let page_size_bytes = libc::sysconf(libc::_SC_PAGESIZE) as u64;
 
-        (stats.active_count as u64
+        let usage = (stats.active_count as u64
             + stats.inactive_count as u64
             + stats.wire_count as u64
             + stats.speculative_count as u64
             + stats.compressor_page_count as u64
             - stats.purgeable_count as u64
             - stats.external_page_count as u64)
-            * page_size_bytes
-    };
+            * page_size_bytes;
+
+        let breakdown = MemoryBreakdown {
+            active: stats.active_count as u64 * page_size_bytes,
+            inactive: stats.inactive_count as u64 * page_size_bytes,
+            wired: stats.wire_count as u64 * page_size_bytes,
+            speculative: stats.speculative_count as u64 * page_size_bytes,
+            compressed: stats.compressor_page_count as u64 * page_size_bytes,
+            purgeable: stats.purgeable_count as u64 * page_size_bytes,
+            external: stats.external_page_count as u64 * page_size_bytes,
+            free: stats.free_count as u64 * page_size_bytes,
+        };
+
+        let counters = MemoryCounters {
+            pageins: stats.pageins,
+            pageouts: stats.pageouts,
+            swapins: stats.swapins,
+            swapouts: stats.swapouts,
+            compressions: stats.compressions,
+            decompressions: stats.decompressions,
+        };
 
-    Ok((usage, total))
+        Ok(RamInfo {
+            total,
+            usage,
+            breakdown,
+            counters,
+        })
+    }
 }
 
+#[cfg(target_os = "macos")]
 fn get_swap_info() -> Result<(u64, u64), Box<dyn Error>> {
     unsafe {
         let mut name = [libc::CTL_VM, libc::VM_SWAPUSAGE];
@@ -96,3 +236,233 @@ fn get_swap_info() -> Result<(u64, u64), Box<dyn Error>> {
         Ok((xsw.xsu_used, xsw.xsu_total))
     }
 }
+
+#[cfg(target_os = "linux")]
+fn get_ram_info() -> Result<RamInfo, Box<dyn Error>> {
+    let page_kib = (unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64 / 1024).max(1);
+    ram_info_from(&read_meminfo()?, &read_vmstat()?, page_kib)
+}
+
+#[cfg(target_os = "linux")]
+fn ram_info_from(
+    meminfo: &HashMap<String, u64>,
+    vmstat: &HashMap<String, u64>,
+    page_kib: u64,
+) -> Result<RamInfo, Box<dyn Error>> {
+    let field = |key: &str| meminfo.get(key).copied().unwrap_or(0);
+    let total = *meminfo
+        .get("MemTotal")
+        .ok_or("Failed to get total memory")?;
+    let available = *meminfo
+        .get("MemAvailable")
+        .ok_or("Failed to get memory stats")?;
+
+    // Closest equivalents of the macOS page states:
+    // wired = memory the kernel cannot reclaim, external = page cache backing files
+    let breakdown = MemoryBreakdown {
+        active: field("Active"),
+        inactive: field("Inactive"),
+        wired: field("Unevictable")
+            + field("SUnreclaim")
+            + field("KernelStack")
+            + field("PageTables"),
+        speculative: 0,
+        compressed: field("Zswap"),
+        purgeable: 0,
+        external: field("Active(file)") + field("Inactive(file)"),
+        free: field("MemFree"),
+    };
+
+    // pgpgin/pgpgout are reported in KiB, the other counters in pages
+    let stat = |key: &str| vmstat.get(key).copied().unwrap_or(0);
+    let counters = MemoryCounters {
+        pageins: stat("pgpgin") / page_kib,
+        pageouts: stat("pgpgout") / page_kib,
+        swapins: stat("pswpin"),
+        swapouts: stat("pswpout"),
+        compressions: stat("zswpout"),
+        decompressions: stat("zswpin"),
+    };
+
+    Ok(RamInfo {
+        total,
+        usage: total.saturating_sub(available),
+        breakdown,
+        counters,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn get_swap_info() -> Result<(u64, u64), Box<dyn Error>> {
+    let meminfo = read_meminfo()?;
+    let total = *meminfo.get("SwapTotal").ok_or("Failed to get swap usage")?;
+    let free = *meminfo.get("SwapFree").ok_or("Failed to get swap usage")?;
+
+    Ok((total.saturating_sub(free), total))
+}
+
+#[cfg(target_os = "linux")]
+fn read_meminfo() -> Result<HashMap<String, u64>, Box<dyn Error>> {
+    let content = std::fs::read_to_string("/proc/meminfo")?;
+    Ok(crate::utils::procfs::parse_key_values(&content))
+}
+
+#[cfg(target_os = "linux")]
+fn read_vmstat() -> Result<HashMap<String, u64>, Box<dyn Error>> {
+    let content = std::fs::read_to_string("/proc/vmstat")?;
+    Ok(parse_vmstat(&content))
+}
+
+#[cfg(target_os = "linux")]
+fn parse_vmstat(content: &str) -> HashMap<String, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn get_memory_pressure(_ram: &RamInfo) -> MemoryPressure {
+    crate::metrics::pressure::vm_pressure_level().unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn get_memory_pressure(ram: &RamInfo) -> MemoryPressure {
+    let stalled = std::fs::read_to_string("/proc/pressure/memory")
+        .ok()
+        .and_then(|content| crate::metrics::pressure::parse_pressure(&content));
+    pressure_level(ram, stalled)
+}
+
+// Prefer stall information; fall back to how much memory is still available
+#[cfg(target_os = "linux")]
+fn pressure_level(
+    ram: &RamInfo,
+    stalled: Option<crate::metrics::pressure::Pressure>,
+) -> MemoryPressure {
+    use crate::metrics::pressure::stall_level;
+
+    match stalled {
+        Some(pressure) => stall_level(&pressure.some),
+        None => {
+            let available = ram.total.saturating_sub(ram.usage) as f64 / ram.total.max(1) as f64;
+            if available < 0.05 {
+                MemoryPressure::Critical
+            } else if available < 0.15 {
+                MemoryPressure::Warning
+            } else {
+                MemoryPressure::Normal
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::metrics::pressure::parse_pressure;
+
+    const MEMINFO: &str = "\
+MemTotal:        8000000 kB
+MemFree:         1000000 kB
+MemAvailable:    3000000 kB
+Active:          2500000 kB
+Inactive:        1500000 kB
+Active(file):     800000 kB
+Inactive(file):   700000 kB
+Unevictable:       10000 kB
+SUnreclaim:        20000 kB
+KernelStack:        5000 kB
+PageTables:        15000 kB
+Zswap:             40000 kB
+SwapTotal:       2000000 kB
+SwapFree:        1500000 kB
+";
+
+    const VMSTAT: &str = "\
+pgpgin 4096
+pgpgout 8192
+pswpin 30
+pswpout 40
+zswpin 5
+zswpout 6
+";
+
+    fn ram(total: u64, usage: u64) -> RamInfo {
+        RamInfo {
+            total,
+            usage,
+            breakdown: MemoryBreakdown::default(),
+            counters: MemoryCounters::default(),
+        }
+    }
+
+    #[test]
+    fn maps_meminfo_and_vmstat() {
+        let meminfo = crate::utils::procfs::parse_key_values(MEMINFO);
+        let ram = ram_info_from(&meminfo, &parse_vmstat(VMSTAT), 4).unwrap();
+        let kib = |n: u64| n * 1024;
+
+        assert_eq!(ram.total, kib(8000000));
+        assert_eq!(ram.usage, kib(5000000)); // total - available
+        let b = &ram.breakdown;
+        assert_eq!((b.active, b.inactive), (kib(2500000), kib(1500000)));
+        assert_eq!(b.wired, kib(10000 + 20000 + 5000 + 15000));
+        assert_eq!(b.compressed, kib(40000));
+        assert_eq!(b.external, kib(800000 + 700000));
+        assert_eq!(b.free, kib(1000000));
+        assert_eq!((b.speculative, b.purgeable), (0, 0));
+
+        // pgpgin/pgpgout are KiB, converted to 4 KiB pages
+        let c = &ram.counters;
+        assert_eq!((c.pageins, c.pageouts), (1024, 2048));
+        assert_eq!((c.swapins, c.swapouts), (30, 40));
+        assert_eq!((c.compressions, c.decompressions), (6, 5));
+    }
+
+    #[test]
+    fn meminfo_without_totals_is_an_error() {
+        let meminfo = crate::utils::procfs::parse_key_values("MemFree: 1000 kB\n");
+        assert!(ram_info_from(&meminfo, &HashMap::new(), 4).is_err());
+    }
+
+    #[test]
+    fn pressure_follows_stalls_then_available_memory() {
+        let stalls = |avg10: &str| {
+            parse_pressure(&format!(
+                "some avg10={} avg60=0.00 avg300=0.00 total=0\n",
+                avg10
+            ))
+        };
+        let plenty = ram(100, 50);
+        assert_eq!(
+            pressure_level(&plenty, stalls("9.99")),
+            MemoryPressure::Normal
+        );
+        assert_eq!(
+            pressure_level(&plenty, stalls("10.00")),
+            MemoryPressure::Warning
+        );
+        assert_eq!(
+            pressure_level(&plenty, stalls("40.00")),
+            MemoryPressure::Critical
+        );
+        // Stalls win over available memory when PSI is present
+        assert_eq!(
+            pressure_level(&ram(100, 99), stalls("0.00")),
+            MemoryPressure::Normal
+        );
+
+        // Without PSI: warning below 15% available, critical below 5%
+        assert_eq!(pressure_level(&ram(100, 85), None), MemoryPressure::Normal);
+        assert_eq!(pressure_level(&ram(100, 86), None), MemoryPressure::Warning);
+        assert_eq!(pressure_level(&ram(100, 95), None), MemoryPressure::Warning);
+        assert_eq!(
+            pressure_level(&ram(100, 96), None),
+            MemoryPressure::Critical
+        );
+    }
+}
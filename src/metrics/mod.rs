@@ -1,9 +1,52 @@
-pub mod cpu;
-pub mod iokit;
-pub mod ioreport_perf;
-pub mod memory;
+pub(crate) mod battery;
+pub(crate) mod cgroup;
+pub(crate) mod chip_profile;
+pub(crate) mod cpu;
+pub(crate) mod cpu_time;
+pub(crate) mod disk;
+pub(crate) mod doctor;
+pub(crate) mod error;
+pub(crate) mod filesystem;
+#[cfg(target_os = "macos")]
+pub(crate) mod iokit;
+#[cfg(target_os = "macos")]
+pub(crate) mod ioreport_perf;
+pub(crate) mod ioreport_sample;
+pub(crate) mod load;
+pub(crate) mod memory;
+pub(crate) mod network;
+pub(crate) mod performance;
+pub(crate) mod power;
+pub(crate) mod pressure;
+pub(crate) mod process;
+pub(crate) mod synthetic;
+pub(crate) mod throttling;
+#[cfg(not(target_os = "macos"))]
+mod unsupported;
 
-pub use cpu::{CpuMetrics, get_cpu_metrics};
-pub use iokit::{PowerMetrics, get_power_metrics_from_sample, get_power_metrics_with_interval};
+pub use battery::{BatteryMetrics, get_battery_metrics};
+pub use cgroup::{CgroupMetrics, CgroupSampler, DEFAULT_CGROUP_ROOT};
+pub use chip_profile::ChipProfiles;
+pub use cpu::{CpuCluster, CpuMetrics, get_cpu_metrics};
+pub use cpu_time::{CpuTimeMetrics, CpuTimeSampler, CpuTimes};
+pub use disk::{DiskMetrics, DiskSampler};
+pub use error::{AtopError, Subsystem, available};
+pub use filesystem::{DEFAULT_FS_WARN_PERCENT, FilesystemMetrics, get_filesystem_metrics};
+#[cfg(target_os = "macos")]
+pub use iokit::{IOReport, capture_channel_dump, get_power_metrics_from_sample};
+#[cfg(target_os = "macos")]
 pub use ioreport_perf::IOReportPerf;
-pub use memory::{MemoryMetrics, get_memory_metrics};
+pub use ioreport_sample::ChannelDump;
+pub use load::{LoadMetrics, LoadSampler};
+pub use memory::{MemoryActivity, MemoryBreakdown, MemoryMetrics, MemoryPressure, MemorySampler};
+pub use network::{InterfaceFilter, NetworkMetrics, NetworkSampler};
+pub use performance::{ClusterSample, PerformanceSample};
+pub use power::PowerMetrics;
+pub use pressure::{Pressure, PressureMetrics, PressureStall, get_pressure_metrics, stall_level};
+pub use process::{ProcessAction, ProcessFilter, ProcessMetrics, ProcessSampler, attribute_energy};
+pub use synthetic::{Scenario, SyntheticSampler, Waveform};
+pub use throttling::{ClusterThrottle, ClusterThrottleSummary, ThrottleDetector, ThrottleSummary};
+#[cfg(not(target_os = "macos"))]
+pub use unsupported::{
+    IOReport, IOReportPerf, capture_channel_dump, get_power_metrics_from_sample,
+};
@@ -0,0 +1,278 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Instant;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct NetworkMetrics {
+    pub name: String,
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+    pub rx_packets_per_sec: f32,
+    pub tx_packets_per_sec: f32,
+    pub rx_errors_per_sec: f32,
+    pub tx_errors_per_sec: f32,
+    pub rx_drops_per_sec: f32,
+    pub tx_drops_per_sec: f32,
+}
+
+// Cumulative per-interface counters as reported by the OS
+#[derive(Debug, Default, Clone)]
+struct InterfaceCounters {
+    rx_bytes: u64,
+    tx_bytes: u64,
+    rx_packets: u64,
+    tx_packets: u64,
+    rx_errors: u64,
+    tx_errors: u64,
+    rx_drops: u64,
+    tx_drops: u64,
+}
+
+// if_data counters on macOS are 32-bit and wrap around
+const COUNTERS_WRAP_AT_32_BITS: bool = cfg!(target_os = "macos");
+
+fn counter_delta(cur: u64, prev: u64, wraps_at_32_bits: bool) -> u64 {
+    if cur >= prev {
+        cur - prev
+    } else if wraps_at_32_bits && prev > u64::from(u32::MAX / 2) {
+        // The counter was in its upper half, so it ran past u32::MAX
+        cur + (1 << 32) - prev
+    } else {
+        // Counter reset (interface re-created)
+        0
+    }
+}
+
+/// Selects which interfaces are reported.
+/// Patterns are comma-separated names; a trailing `*` matches any suffix (e.g. `en*,utun0`).
+/// Without patterns every interface except loopback (`lo`, `lo0`) is reported.
+#[derive(Debug, Default, Clone)]
+pub struct InterfaceFilter {
+    patterns: Vec<String>,
+}
+
+impl InterfaceFilter {
+    pub fn parse(list: &str) -> Self {
+        Self {
+            patterns: list
+                .split(',')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        if self.patterns.is_empty() {
+            return !matches!(name, "lo" | "lo0");
+        }
+        self.patterns.iter().any(|p| match p.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == p,
+        })
+    }
+}
+
+/// Turns cumulative interface counters into per-second rates between samples
+pub struct NetworkSampler {
+    filter: InterfaceFilter,
+    prev: HashMap<String, InterfaceCounters>,
+    prev_time: Instant,
+}
+
+impl NetworkSampler {
+    pub fn new(filter: InterfaceFilter) -> Self {
+        Self {
+            filter,
+            prev: read_interface_counters().unwrap_or_default(),
+            prev_time: Instant::now(),
+        }
+    }
+
+    /// Rates since the previous call (or since construction for the first call)
+    pub fn sample(&mut self) -> Result<Vec<NetworkMetrics>, AtopError> {
+        let counters = read_interface_counters().subsystem(Subsystem::Network)?;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.prev_time).as_secs_f64();
+
+        let mut interfaces: Vec<NetworkMetrics> = counters
+            .iter()
+            .filter(|(name, _)| self.filter.matches(name))
+            .map(|(name, cur)| {
+                let prev = self.prev.get(name).cloned().unwrap_or_else(|| cur.clone());
+                let rate = |cur: u64, prev: u64| {
+                    if elapsed > 0.0 {
+                        counter_delta(cur, prev, COUNTERS_WRAP_AT_32_BITS) as f64 / elapsed
+                    } else {
+                        0.0
+                    }
+                };
+                NetworkMetrics {
+                    name: name.clone(),
+                    rx_bytes_per_sec: rate(cur.rx_bytes, prev.rx_bytes) as u64,
+                    tx_bytes_per_sec: rate(cur.tx_bytes, prev.tx_bytes) as u64,
+                    rx_packets_per_sec: rate(cur.rx_packets, prev.rx_packets) as f32,
+                    tx_packets_per_sec: rate(cur.tx_packets, prev.tx_packets) as f32,
+                    rx_errors_per_sec: rate(cur.rx_errors, prev.rx_errors) as f32,
+                    tx_errors_per_sec: rate(cur.tx_errors, prev.tx_errors) as f32,
+                    rx_drops_per_sec: rate(cur.rx_drops, prev.rx_drops) as f32,
+                    tx_drops_per_sec: rate(cur.tx_drops, prev.tx_drops) as f32,
+                }
+            })
+            .collect();
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.prev = counters;
+        self.prev_time = now;
+
+        Ok(interfaces)
+    }
+}
+
+impl Default for NetworkSampler {
+    fn default() -> Self {
+        Self::new(InterfaceFilter::default())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_interface_counters() -> Result<HashMap<String, InterfaceCounters>, Box<dyn Error>> {
+    let mut interfaces = HashMap::new();
+
+    unsafe {
+        let mut ifap: *mut libc::ifaddrs = std::ptr::null_mut();
+        if libc::getifaddrs(&mut ifap) != 0 {
+            return Err("Failed to get interface addresses".into());
+        }
+
+        // Link-level (AF_LINK) entries carry the if_data statistics
+        let mut cur = ifap;
+        while !cur.is_null() {
+            let ifa = &*cur;
+            cur = ifa.ifa_next;
+
+            if ifa.ifa_addr.is_null()
+                || ifa.ifa_data.is_null()
+                || (*ifa.ifa_addr).sa_family as i32 != libc::AF_LINK
+            {
+                continue;
+            }
+
+            let name = std::ffi::CStr::from_ptr(ifa.ifa_name)
+                .to_string_lossy()
+                .to_string();
+            let data = &*(ifa.ifa_data as *const libc::if_data);
+            interfaces.insert(
+                name,
+                InterfaceCounters {
+                    rx_bytes: data.ifi_ibytes as u64,
+                    tx_bytes: data.ifi_obytes as u64,
+                    rx_packets: data.ifi_ipackets as u64,
+                    tx_packets: data.ifi_opackets as u64,
+                    rx_errors: data.ifi_ierrors as u64,
+                    tx_errors: data.ifi_oerrors as u64,
+                    rx_drops: data.ifi_iqdrops as u64,
+                    // if_data has no transmit drop counter
+                    tx_drops: 0,
+                },
+            );
+        }
+
+        libc::freeifaddrs(ifap);
+    }
+
+    Ok(interfaces)
+}
+
+#[cfg(target_os = "linux")]
+fn read_interface_counters() -> Result<HashMap<String, InterfaceCounters>, Box<dyn Error>> {
+    let content = std::fs::read_to_string("/proc/net/dev")?;
+    Ok(parse_net_dev(&content))
+}
+
+#[cfg(target_os = "linux")]
+fn parse_net_dev(content: &str) -> HashMap<String, InterfaceCounters> {
+    let mut interfaces = HashMap::new();
+
+    // Skip the two header lines
+    for line in content.lines().skip(2) {
+        let Some((name, stats)) = line.split_once(':') else {
+            continue;
+        };
+        let fields: Vec<u64> = stats
+            .split_whitespace()
+            .map(|f| f.parse().unwrap_or(0))
+            .collect();
+        if fields.len() < 12 {
+            continue;
+        }
+
+        interfaces.insert(
+            name.trim().to_string(),
+            InterfaceCounters {
+                rx_bytes: fields[0],
+                rx_packets: fields[1],
+                rx_errors: fields[2],
+                rx_drops: fields[3],
+                tx_bytes: fields[8],
+                tx_packets: fields[9],
+                tx_errors: fields[10],
+                tx_drops: fields[11],
+            },
+        );
+    }
+    interfaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_matches_names_and_prefixes() {
+        let all = InterfaceFilter::default();
+        assert!(all.matches("en0") && all.matches("eth0"));
+        assert!(!all.matches("lo") && !all.matches("lo0"));
+        // Only the loopback itself is hidden, not every name starting with "lo"
+        assert!(all.matches("lowpan0") && all.matches("lo1"));
+
+        let filter = InterfaceFilter::parse(" en*, utun0 ,,");
+        assert!(filter.matches("en0") && filter.matches("en10"));
+        assert!(filter.matches("utun0"));
+        assert!(!filter.matches("utun1") && !filter.matches("bridge0"));
+        // An explicit pattern can select loopback
+        assert!(InterfaceFilter::parse("lo*").matches("lo0"));
+        assert!(!InterfaceFilter::parse("en").matches("en0"));
+    }
+
+    #[test]
+    fn counters_wrap_only_near_the_32_bit_limit() {
+        assert_eq!(counter_delta(150, 100, true), 50);
+        // A 32-bit counter near its limit wrapped around
+        assert_eq!(counter_delta(10, u64::from(u32::MAX) - 9, true), 20);
+        // A counter far from the limit went back because the interface was re-created
+        assert_eq!(counter_delta(10, 5_000_000, true), 0);
+        assert_eq!(counter_delta(10, u64::from(u32::MAX) - 9, false), 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_proc_net_dev() {
+        let content = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    1200      12    0    0    0     0          0         0     1200      12    0    0    0     0       0          0
+  eth0: 5000000    4000    1    2    0     0          0        10   300000    2500    3    4    0     0       0          0
+ wlan0: short line
+";
+        let interfaces = parse_net_dev(content);
+        assert_eq!(interfaces.len(), 2);
+        let eth0 = &interfaces["eth0"];
+        assert_eq!((eth0.rx_bytes, eth0.rx_packets), (5000000, 4000));
+        assert_eq!((eth0.rx_errors, eth0.rx_drops), (1, 2));
+        assert_eq!((eth0.tx_bytes, eth0.tx_packets), (300000, 2500));
+        assert_eq!((eth0.tx_errors, eth0.tx_drops), (3, 4));
+    }
+}
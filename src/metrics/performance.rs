@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+// Performance sample shared by the IOReport collector and the UI
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct PerformanceSample {
+    pub ecpu_usage: (u32, f32),       // (freq_mhz, utilization_percent)
+    pub pcpu_usage: (u32, f32),       // (freq_mhz, utilization_percent)
+    pub gpu_usage: (u32, f32),        // (freq_mhz, utilization_percent)
+    pub ecpu_active: f32,             // share of time in an active state, 0.0 - 1.0
+    pub pcpu_active: f32,             // share of time in an active state, 0.0 - 1.0
+    pub clusters: Vec<ClusterSample>, // per cluster, in CpuMetrics::clusters order
+}
+
+// Frequency and utilization of one CPU cluster, averaged over its cores
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ClusterSample {
+    pub id: String,
+    pub freq_mhz: u32,
+    pub utilization: f32, // same scale as PerformanceSample::ecpu_usage.1
+    pub active: f32,      // share of time in an active state, 0.0 - 1.0
+}
@@ -0,0 +1,25 @@
+use crate::metrics::battery::BatteryMetrics;
+use serde::Serialize;
+
+// Power metrics structure
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct PowerMetrics {
+    pub cpu_power: f32,     // Watts
+    pub ecpu_power: f32,    // Watts, E-cluster share of cpu_power
+    pub pcpu_power: f32,    // Watts, P-cluster share of cpu_power
+    pub gpu_power: f32,     // Watts
+    pub ane_power: f32,     // Watts (Apple Neural Engine)
+    pub ram_power: f32,     // Watts
+    pub gpu_ram_power: f32, // Watts
+    pub all_power: f32,     // Combined CPU+GPU+ANE
+    pub sys_power: f32,     // Total system power (battery/adapter telemetry when available)
+}
+
+impl PowerMetrics {
+    /// Use measured whole-system power instead of the CPU+GPU+ANE estimate when available
+    pub fn apply_system_power(&mut self, battery: Option<&BatteryMetrics>) {
+        if let Some(watts) = battery.and_then(|b| b.system_power_watts) {
+            self.sys_power = watts;
+        }
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(target_os = "macos")]
+use crate::metrics::error::ResultExt;
+use crate::metrics::error::{AtopError, Subsystem};
+use crate::metrics::memory::MemoryPressure;
+use serde::Serialize;
+#[cfg(target_os = "macos")]
+use std::error::Error;
+
+// One line of a PSI file: share of time stalled over 10s/60s/300s windows
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct PressureStall {
+    pub avg10: f32,    // percent
+    pub avg60: f32,    // percent
+    pub avg300: f32,   // percent
+    pub total_us: u64, // cumulative stall time in microseconds
+}
+
+// "some": at least one task stalled, "full": all non-idle tasks stalled (not reported for cpu on older kernels)
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct Pressure {
+    pub some: PressureStall,
+    pub full: Option<PressureStall>,
+}
+
+// System-wide stalls. Only Linux has PSI; macOS reports just the kernel's memory pressure level
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct PressureMetrics {
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+    pub memory_level: MemoryPressure,
+}
+
+/// Parse the contents of a PSI file (`/proc/pressure/*` or a cgroup's `*.pressure`)
+pub fn parse_pressure(content: &str) -> Option<Pressure> {
+    let mut some = None;
+    let mut full = None;
+
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+        let mut stall = PressureStall::default();
+        for field in fields {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            match key {
+                "avg10" => stall.avg10 = value.parse().unwrap_or(0.0),
+                "avg60" => stall.avg60 = value.parse().unwrap_or(0.0),
+                "avg300" => stall.avg300 = value.parse().unwrap_or(0.0),
+                "total" => stall.total_us = value.parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+        match kind {
+            Some("some") => some = Some(stall),
+            Some("full") => full = Some(stall),
+            _ => {}
+        }
+    }
+
+    Some(Pressure { some: some?, full })
+}
+
+/// Level for a stall line: warning once tasks waited 10% of the last 10s, critical from 40%
+pub fn stall_level(stall: &PressureStall) -> MemoryPressure {
+    if stall.avg10 >= 40.0 {
+        MemoryPressure::Critical
+    } else if stall.avg10 >= 10.0 {
+        MemoryPressure::Warning
+    } else {
+        MemoryPressure::Normal
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn vm_pressure_level() -> Result<MemoryPressure, Box<dyn Error>> {
+    // Same levels the kernel uses for memory pressure notifications
+    match crate::utils::iokit_utils::sysctl_u64("kern.memorystatus_vm_pressure_level")? {
+        4 => Ok(MemoryPressure::Critical),
+        2 => Ok(MemoryPressure::Warning),
+        _ => Ok(MemoryPressure::Normal),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_pressure_metrics() -> Result<PressureMetrics, AtopError> {
+    Ok(PressureMetrics {
+        memory_level: vm_pressure_level().subsystem(Subsystem::Pressure)?,
+        ..Default::default()
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_pressure_metrics() -> Result<PressureMetrics, AtopError> {
+    let read = |name: &str| {
+        std::fs::read_to_string(format!("/proc/pressure/{}", name))
+            .ok()
+            .and_then(|content| parse_pressure(&content))
+    };
+    let (cpu, memory, io) = (read("cpu"), read("memory"), read("io"));
+    if cpu.is_none() && memory.is_none() && io.is_none() {
+        return Err(AtopError::unavailable(
+            Subsystem::Pressure,
+            "/proc/pressure is not available (kernel without CONFIG_PSI or psi=0)",
+        ));
+    }
+
+    Ok(PressureMetrics {
+        memory_level: memory
+            .as_ref()
+            .map(|p| stall_level(&p.some))
+            .unwrap_or_default(),
+        cpu,
+        memory,
+        io,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_some_and_full_lines() {
+        let memory = parse_pressure(
+            "some avg10=12.34 avg60=5.67 avg300=1.23 total=987654\n\
+             full avg10=3.21 avg60=0.50 avg300=0.10 total=12345\n",
+        )
+        .unwrap();
+        let some = &memory.some;
+        assert_eq!((some.avg10, some.avg60, some.avg300), (12.34, 5.67, 1.23));
+        assert_eq!(some.total_us, 987654);
+        let full = memory.full.unwrap();
+        assert_eq!((full.avg10, full.avg60, full.avg300), (3.21, 0.5, 0.1));
+        assert_eq!(full.total_us, 12345);
+
+        // Kernels before 5.13 have no "full" line for cpu
+        let cpu = parse_pressure("some avg10=0.00 avg60=0.00 avg300=0.00 total=42\n").unwrap();
+        assert_eq!(cpu.some.total_us, 42);
+        assert!(cpu.full.is_none());
+
+        assert!(parse_pressure("").is_none());
+        assert!(parse_pressure("full avg10=1.00 avg60=0.00 avg300=0.00 total=1\n").is_none());
+    }
+
+    #[test]
+    fn stall_levels_start_at_10_and_40_percent() {
+        let level = |avg10| {
+            stall_level(&PressureStall {
+                avg10,
+                ..Default::default()
+            })
+        };
+        assert_eq!(level(9.9), MemoryPressure::Normal);
+        assert_eq!(level(10.0), MemoryPressure::Warning);
+        assert_eq!(level(39.9), MemoryPressure::Warning);
+        assert_eq!(level(40.0), MemoryPressure::Critical);
+    }
+}
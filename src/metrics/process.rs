@@ -0,0 +1,869 @@
+use crate::metrics::error::{AtopError, ResultExt, Subsystem};
+use crate::metrics::power::PowerMetrics;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Instant;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct ProcessMetrics {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub command: String, // full command line, or the name when it cannot be read
+    pub user: String,
+    pub cpu_percent: f32,          // 100% = one core fully busy
+    pub pcpu_percent: Option<f32>, // part of cpu_percent spent on P-cores, when reported
+    pub rss_bytes: u64,
+    pub threads: u32,
+    pub nice: i32,
+    pub state: String,
+    pub energy_watts: Option<f32>, // estimated share of CPU power
+    pub disk_read_bytes_per_sec: Option<u64>, // None when the OS does not expose it for this process
+    pub disk_write_bytes_per_sec: Option<u64>,
+}
+
+// Raw per-process values as reported by the OS
+#[derive(Debug, Default, Clone)]
+struct ProcessInfo {
+    pid: u32,
+    ppid: u32,
+    name: String,
+    uid: u32,
+    cpu_time_ns: u64,          // cumulative user + system time
+    pcpu_time_ns: Option<u64>, // part of cpu_time_ns spent on P-cores
+    rss_bytes: u64,
+    threads: u32,
+    nice: i32,
+    state: &'static str,
+    disk_read_bytes: Option<u64>, // cumulative storage I/O
+    disk_write_bytes: Option<u64>,
+}
+
+/// Lists processes with CPU usage and I/O rates computed from deltas between samples
+pub struct ProcessSampler {
+    prev: HashMap<u32, ProcessInfo>,
+    prev_time: Instant,
+    users: HashMap<u32, String>,
+    commands: HashMap<u32, String>,
+}
+
+impl ProcessSampler {
+    pub fn new() -> Self {
+        Self {
+            prev: read_processes()
+                .map(|procs| procs.into_iter().map(|p| (p.pid, p)).collect())
+                .unwrap_or_default(),
+            prev_time: Instant::now(),
+            users: HashMap::new(),
+            commands: HashMap::new(),
+        }
+    }
+
+    /// Processes alive now, with CPU usage and I/O rates since the previous call
+    pub fn sample(&mut self) -> Result<Vec<ProcessMetrics>, AtopError> {
+        let procs: HashMap<u32, ProcessInfo> = read_processes()
+            .subsystem(Subsystem::Process)?
+            .into_iter()
+            .map(|p| (p.pid, p))
+            .collect();
+        let now = Instant::now();
+        let elapsed_ns = now.duration_since(self.prev_time).as_nanos() as f64;
+
+        // Command lines rarely change, so only read them for new processes
+        self.commands.retain(|pid, _| procs.contains_key(pid));
+
+        let mut processes: Vec<ProcessMetrics> = procs
+            .values()
+            .map(|p| {
+                // Processes started since the previous sample count from zero
+                let prev = self.prev.get(&p.pid);
+                let delta = |cur: u64, prev: Option<u64>| cur.saturating_sub(prev.unwrap_or(0));
+                let percent = |cur: u64, prev: Option<u64>| {
+                    if elapsed_ns > 0.0 {
+                        (delta(cur, prev) as f64 / elapsed_ns * 100.0) as f32
+                    } else {
+                        0.0
+                    }
+                };
+                let per_sec = |cur: u64, prev: Option<u64>| {
+                    if elapsed_ns > 0.0 {
+                        (delta(cur, prev) as f64 * 1e9 / elapsed_ns) as u64
+                    } else {
+                        0
+                    }
+                };
+                ProcessMetrics {
+                    pid: p.pid,
+                    ppid: p.ppid,
+                    name: p.name.clone(),
+                    command: self
+                        .commands
+                        .entry(p.pid)
+                        .or_insert_with(|| read_command(p.pid).unwrap_or_else(|| p.name.clone()))
+                        .clone(),
+                    user: self
+                        .users
+                        .entry(p.uid)
+                        .or_insert_with(|| user_name(p.uid))
+                        .clone(),
+                    cpu_percent: percent(p.cpu_time_ns, prev.map(|prev| prev.cpu_time_ns)),
+                    pcpu_percent: p
+                        .pcpu_time_ns
+                        .map(|cur| percent(cur, prev.and_then(|prev| prev.pcpu_time_ns))),
+                    rss_bytes: p.rss_bytes,
+                    threads: p.threads,
+                    nice: p.nice,
+                    state: p.state.to_string(),
+                    energy_watts: None,
+                    disk_read_bytes_per_sec: p
+                        .disk_read_bytes
+                        .map(|cur| per_sec(cur, prev.and_then(|prev| prev.disk_read_bytes))),
+                    disk_write_bytes_per_sec: p
+                        .disk_write_bytes
+                        .map(|cur| per_sec(cur, prev.and_then(|prev| prev.disk_write_bytes))),
+                }
+            })
+            .collect();
+        processes.sort_by_key(|p| p.pid);
+
+        self.prev = procs;
+        self.prev_time = now;
+
+        Ok(processes)
+    }
+}
+
+impl Default for ProcessSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Something that can be done to a process from the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessAction {
+    Terminate,
+    Kill,
+    Stop,
+    Continue,
+    Renice(i32), // new nice value
+}
+
+impl ProcessAction {
+    pub fn label(self) -> String {
+        match self {
+            ProcessAction::Terminate => "SIGTERM".to_string(),
+            ProcessAction::Kill => "SIGKILL".to_string(),
+            ProcessAction::Stop => "SIGSTOP".to_string(),
+            ProcessAction::Continue => "SIGCONT".to_string(),
+            ProcessAction::Renice(nice) => format!("renice to {}", nice),
+        }
+    }
+
+    /// Send the signal or change the priority via kill(2) / setpriority(2)
+    pub fn apply(self, pid: u32) -> Result<(), AtopError> {
+        // pid 0 or negative values would target whole process groups
+        if pid == 0 || pid > i32::MAX as u32 {
+            return Err(AtopError::read(
+                Subsystem::Process,
+                format!("Invalid PID {}", pid),
+            ));
+        }
+
+        let ret = unsafe {
+            match self {
+                ProcessAction::Terminate => libc::kill(pid as libc::pid_t, libc::SIGTERM),
+                ProcessAction::Kill => libc::kill(pid as libc::pid_t, libc::SIGKILL),
+                ProcessAction::Stop => libc::kill(pid as libc::pid_t, libc::SIGSTOP),
+                ProcessAction::Continue => libc::kill(pid as libc::pid_t, libc::SIGCONT),
+                ProcessAction::Renice(nice) => {
+                    libc::setpriority(libc::PRIO_PROCESS as _, pid as libc::id_t, nice)
+                }
+            }
+        };
+        if ret != 0 {
+            // EPERM means the process isn't ours to signal
+            let err = std::io::Error::last_os_error();
+            return Err(AtopError::from_source(Subsystem::Process, err.into()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Selects processes from a filter expression of space-separated terms, all of which must match:
+/// `user:NAME`, `name:TEXT`, `state:STATE`, `pid:N`, comparisons such as `cpu>10`, `mem>500M`,
+/// `threads>=8`, `power>1` or `write>1M` (bytes/s), and bare words matched against the name and command line.
+#[derive(Debug, Default, Clone)]
+pub struct ProcessFilter {
+    terms: Vec<FilterTerm>,
+}
+
+#[derive(Debug, Clone)]
+enum FilterTerm {
+    Text(String),
+    User(String),
+    Name(String),
+    State(String),
+    Pid(u32),
+    Compare(FilterField, Ordering, f64, bool), // field, direction, value, or-equal
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FilterField {
+    Cpu,
+    Memory,
+    Threads,
+    Power,
+    DiskRead,
+    DiskWrite,
+}
+
+impl ProcessFilter {
+    pub fn parse(expr: &str) -> Self {
+        Self {
+            terms: expr.split_whitespace().map(parse_term).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, process: &ProcessMetrics) -> bool {
+        self.terms.iter().all(|term| match term {
+            FilterTerm::Text(text) => {
+                process.name.to_lowercase().contains(text)
+                    || process.command.to_lowercase().contains(text)
+            }
+            FilterTerm::User(user) => process.user.to_lowercase() == *user,
+            FilterTerm::Name(name) => process.name.to_lowercase().contains(name),
+            FilterTerm::State(state) => process.state.starts_with(state.as_str()),
+            FilterTerm::Pid(pid) => process.pid == *pid,
+            FilterTerm::Compare(field, direction, value, or_equal) => {
+                let actual = match field {
+                    FilterField::Cpu => process.cpu_percent as f64,
+                    FilterField::Memory => process.rss_bytes as f64,
+                    FilterField::Threads => process.threads as f64,
+                    FilterField::Power => process.energy_watts.unwrap_or(0.0) as f64,
+                    FilterField::DiskRead => process.disk_read_bytes_per_sec.unwrap_or(0) as f64,
+                    FilterField::DiskWrite => process.disk_write_bytes_per_sec.unwrap_or(0) as f64,
+                };
+                match actual.partial_cmp(value) {
+                    Some(Ordering::Equal) => *or_equal,
+                    Some(ordering) => ordering == *direction,
+                    None => false,
+                }
+            }
+        })
+    }
+}
+
+fn parse_term(term: &str) -> FilterTerm {
+    let lower = term.to_lowercase();
+
+    if let Some((key, value)) = lower.split_once(':') {
+        match key {
+            "user" | "u" => return FilterTerm::User(value.to_string()),
+            "name" | "n" => return FilterTerm::Name(value.to_string()),
+            "state" | "s" => return FilterTerm::State(value.to_string()),
+            "pid" => {
+                if let Ok(pid) = value.parse() {
+                    return FilterTerm::Pid(pid);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Comparisons: field followed by >, >=, <, <= or = and a number
+    if let Some(pos) = lower.find(['>', '<', '=']) {
+        let (key, rest) = lower.split_at(pos);
+        let field = match key {
+            "cpu" => Some(FilterField::Cpu),
+            "mem" | "rss" => Some(FilterField::Memory),
+            "threads" | "thr" => Some(FilterField::Threads),
+            "power" | "energy" => Some(FilterField::Power),
+            "read" => Some(FilterField::DiskRead),
+            "write" => Some(FilterField::DiskWrite),
+            _ => None,
+        };
+        let (direction, or_equal, value) = if let Some(v) = rest.strip_prefix(">=") {
+            (Ordering::Greater, true, v)
+        } else if let Some(v) = rest.strip_prefix("<=") {
+            (Ordering::Less, true, v)
+        } else if let Some(v) = rest.strip_prefix('>') {
+            (Ordering::Greater, false, v)
+        } else if let Some(v) = rest.strip_prefix('<') {
+            (Ordering::Less, false, v)
+        } else {
+            // Plain `=`: only equality matches
+            (Ordering::Equal, true, &rest[1..])
+        };
+        if let Some(field) = field
+            && let Some(value) = parse_filter_value(field, value)
+        {
+            return FilterTerm::Compare(field, direction, value, or_equal);
+        }
+    }
+
+    FilterTerm::Text(lower)
+}
+
+// Byte values (memory, disk rates) accept K/M/G suffixes
+fn parse_filter_value(field: FilterField, value: &str) -> Option<f64> {
+    if let FilterField::Memory | FilterField::DiskRead | FilterField::DiskWrite = field {
+        let (number, scale) = match value.chars().last()? {
+            'k' => (&value[..value.len() - 1], 1024.0),
+            'm' => (&value[..value.len() - 1], 1024.0 * 1024.0),
+            'g' => (&value[..value.len() - 1], 1024.0 * 1024.0 * 1024.0),
+            _ => (value, 1.0),
+        };
+        return number.parse::<f64>().ok().map(|n| n * scale);
+    }
+    value.parse().ok()
+}
+
+/// Estimate per-process power by splitting cluster power in proportion to each
+/// process's time on E-cores and P-cores during the sample
+pub fn attribute_energy(processes: &mut [ProcessMetrics], power: &PowerMetrics) {
+    // Processes without a P-core split follow the split of those that have one
+    let (known_e, known_p) = processes
+        .iter()
+        .filter_map(|p| p.pcpu_percent.map(|pcpu| (p.cpu_percent - pcpu, pcpu)))
+        .fold((0.0, 0.0), |(e, p), (pe, pp)| (e + pe.max(0.0), p + pp));
+    let p_ratio = if known_e + known_p > 0.0 {
+        Some(known_p / (known_e + known_p))
+    } else {
+        None
+    };
+
+    // Fall back to a single pool when per-cluster power or time is not known
+    let split = power.ecpu_power + power.pcpu_power > 0.0 && p_ratio.is_some();
+    let weights: Vec<(f32, f32)> = processes
+        .iter()
+        .map(|p| {
+            if !split {
+                return (p.cpu_percent, 0.0);
+            }
+            let pcpu = p
+                .pcpu_percent
+                .unwrap_or(p.cpu_percent * p_ratio.unwrap_or(0.0));
+            ((p.cpu_percent - pcpu).max(0.0), pcpu)
+        })
+        .collect();
+    let (e_pool, p_pool) = if split {
+        (power.ecpu_power, power.pcpu_power)
+    } else {
+        (power.cpu_power, 0.0)
+    };
+    let e_total: f32 = weights.iter().map(|w| w.0).sum();
+    let p_total: f32 = weights.iter().map(|w| w.1).sum();
+
+    for (process, (e, p)) in processes.iter_mut().zip(weights) {
+        let mut watts = 0.0;
+        if e_total > 0.0 {
+            watts += e_pool * e / e_total;
+        }
+        if p_total > 0.0 {
+            watts += p_pool * p / p_total;
+        }
+        process.energy_watts = Some(watts);
+    }
+}
+
+// Resolve a uid to a login name, falling back to the numeric id
+fn user_name(uid: u32) -> String {
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 1024];
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+
+    let ret = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if ret != 0 || result.is_null() {
+        return uid.to_string();
+    }
+
+    unsafe { std::ffi::CStr::from_ptr(pwd.pw_name) }
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(target_os = "macos")]
+fn read_processes() -> Result<Vec<ProcessInfo>, Box<dyn Error>> {
+    use std::mem;
+
+    // Task times are in mach absolute time units
+    let mut timebase = libc::mach_timebase_info { numer: 0, denom: 0 };
+    unsafe { libc::mach_timebase_info(&mut timebase) };
+    let (numer, denom) = if timebase.denom > 0 {
+        (timebase.numer as u64, timebase.denom as u64)
+    } else {
+        (1, 1)
+    };
+
+    // Ask for the count first, then leave room for processes started in between
+    let count = unsafe { libc::proc_listallpids(std::ptr::null_mut(), 0) };
+    if count <= 0 {
+        return Err("Failed to list processes".into());
+    }
+    let mut pids = vec![0 as libc::c_int; count as usize + 64];
+    let count = unsafe {
+        libc::proc_listallpids(
+            pids.as_mut_ptr() as *mut libc::c_void,
+            (pids.len() * mem::size_of::<libc::c_int>()) as libc::c_int,
+        )
+    };
+    if count <= 0 {
+        return Err("Failed to list processes".into());
+    }
+    pids.truncate(count as usize);
+
+    let mut processes = Vec::with_capacity(pids.len());
+    for pid in pids.into_iter().filter(|&pid| pid > 0) {
+        let mut info: libc::proc_taskallinfo = unsafe { mem::zeroed() };
+        let size = mem::size_of::<libc::proc_taskallinfo>() as libc::c_int;
+        let ret = unsafe {
+            libc::proc_pidinfo(
+                pid,
+                libc::PROC_PIDTASKALLINFO,
+                0,
+                &mut info as *mut _ as *mut libc::c_void,
+                size,
+            )
+        };
+
+        // Task info of other users' processes needs root; BSD info is always readable
+        let (bsd, task) = if ret == size {
+            (info.pbsd, Some(info.ptinfo))
+        } else {
+            let mut bsd: libc::proc_bsdinfo = unsafe { mem::zeroed() };
+            let size = mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+            let ret = unsafe {
+                libc::proc_pidinfo(
+                    pid,
+                    libc::PROC_PIDTBSDINFO,
+                    0,
+                    &mut bsd as *mut _ as *mut libc::c_void,
+                    size,
+                )
+            };
+            if ret != size {
+                continue;
+            }
+            (bsd, None)
+        };
+
+        let name = c_chars_to_string(&bsd.pbi_name);
+        let name = if name.is_empty() {
+            c_chars_to_string(&bsd.pbi_comm)
+        } else {
+            name
+        };
+
+        // SRUN only means the process is alive; check whether any thread is on a CPU
+        let state = match bsd.pbi_status {
+            libc::SRUN if task.is_some_and(|t| t.pti_numrunning > 0) => "running",
+            libc::SRUN | libc::SSLEEP => "sleeping",
+            libc::SIDL => "idle",
+            libc::SSTOP => "stopped",
+            libc::SZOMB => "zombie",
+            _ => "unknown",
+        };
+
+        // Apple Silicon reports the P-core share of CPU time in rusage v6,
+        // disk I/O comes from the v4 part
+        let usage = task.and_then(|_| {
+            let mut usage: RusageInfoV6 = unsafe { mem::zeroed() };
+            let ret = unsafe {
+                libc::proc_pid_rusage(
+                    pid,
+                    RUSAGE_INFO_V6,
+                    &mut usage as *mut _ as *mut libc::rusage_info_t,
+                )
+            };
+            (ret == 0).then_some(usage)
+        });
+
+        processes.push(ProcessInfo {
+            pid: pid as u32,
+            ppid: bsd.pbi_ppid,
+            name,
+            uid: bsd.pbi_uid,
+            cpu_time_ns: task
+                .map(|t| (t.pti_total_user + t.pti_total_system) * numer / denom)
+                .unwrap_or(0),
+            pcpu_time_ns: usage
+                .as_ref()
+                .map(|u| (u.ri_user_ptime + u.ri_system_ptime) * numer / denom),
+            rss_bytes: task.map(|t| t.pti_resident_size).unwrap_or(0),
+            threads: task.map(|t| t.pti_threadnum.max(0) as u32).unwrap_or(0),
+            nice: bsd.pbi_nice,
+            state,
+            disk_read_bytes: usage.as_ref().map(|u| u.ri_v4.ri_diskio_bytesread),
+            disk_write_bytes: usage.as_ref().map(|u| u.ri_v4.ri_diskio_byteswritten),
+        });
+    }
+
+    Ok(processes)
+}
+
+// Arguments are read from KERN_PROCARGS2: argc, the executable path, padding, then argv
+#[cfg(target_os = "macos")]
+fn read_command(pid: u32) -> Option<String> {
+    let mut mib = [libc::CTL_KERN, libc::KERN_ARGMAX];
+    let mut argmax: libc::c_int = 0;
+    let mut size = std::mem::size_of::<libc::c_int>();
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            2,
+            &mut argmax as *mut _ as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 || argmax <= 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u8; argmax as usize];
+    let mut size = buf.len();
+    let mut mib = [libc::CTL_KERN, libc::KERN_PROCARGS2, pid as libc::c_int];
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            3,
+            buf.as_mut_ptr() as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 || size < 4 {
+        return None;
+    }
+
+    let argc = i32::from_ne_bytes(buf[..4].try_into().ok()?).max(0) as usize;
+    let mut rest = buf[4..size].splitn(2, |&b| b == 0).nth(1)?;
+    while let [0, tail @ ..] = rest {
+        rest = tail;
+    }
+    let args: Vec<String> = rest
+        .split(|&b| b == 0)
+        .take(argc)
+        .map(|arg| String::from_utf8_lossy(arg).to_string())
+        .collect();
+
+    let command = args.join(" ");
+    (!command.is_empty()).then_some(command)
+}
+
+#[cfg(target_os = "macos")]
+const RUSAGE_INFO_V6: libc::c_int = 6;
+
+// struct rusage_info_v6 from <sys/resource.h>; libc only goes up to v4
+#[cfg(target_os = "macos")]
+#[repr(C)]
+#[allow(dead_code)]
+struct RusageInfoV6 {
+    ri_v4: libc::rusage_info_v4,
+    ri_flags: u64,
+    ri_user_ptime: u64,
+    ri_system_ptime: u64,
+    ri_pinstructions: u64,
+    ri_pcycles: u64,
+    ri_energy_nj: u64,
+    ri_penergy_nj: u64,
+    ri_secure_time_in_system: u64,
+    ri_secure_ptime_in_system: u64,
+    ri_neural_footprint: u64,
+    ri_lifetime_max_neural_footprint: u64,
+    ri_interval_max_neural_footprint: u64,
+    ri_reserved: [u64; 9],
+}
+
+#[cfg(target_os = "macos")]
+fn c_chars_to_string(chars: &[libc::c_char]) -> String {
+    let bytes: Vec<u8> = chars
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+#[cfg(target_os = "linux")]
+fn read_processes() -> Result<Vec<ProcessInfo>, Box<dyn Error>> {
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+
+    let mut processes = Vec::new();
+    for entry in std::fs::read_dir("/proc")?.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        // Processes can exit between listing and reading
+        if let Some(info) = read_process(pid, ticks_per_sec, page_size) {
+            processes.push(info);
+        }
+    }
+
+    Ok(processes)
+}
+
+#[cfg(target_os = "linux")]
+fn read_process(pid: u32, ticks_per_sec: u64, page_size: u64) -> Option<ProcessInfo> {
+    let dir = std::path::Path::new("/proc").join(pid.to_string());
+    let stat = std::fs::read_to_string(dir.join("stat")).ok()?;
+
+    // The command name is in parentheses and may itself contain spaces or parentheses
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat[open + 1..close].to_string();
+    let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+    if fields.len() < 22 {
+        return None;
+    }
+    let field = |i: usize| fields[i].parse::<u64>().unwrap_or(0);
+
+    let state = match fields[0] {
+        "R" => "running",
+        "S" => "sleeping",
+        "D" => "disk",
+        "Z" => "zombie",
+        "T" | "t" => "stopped",
+        "I" => "idle",
+        "X" | "x" => "dead",
+        _ => "unknown",
+    };
+
+    let uid = std::fs::read_to_string(dir.join("status"))
+        .ok()
+        .and_then(|status| {
+            let line = status.lines().find(|l| l.starts_with("Uid:"))?;
+            line.split_whitespace().nth(1)?.parse().ok()
+        })
+        .unwrap_or(0);
+
+    // Only readable for our own processes unless running as root
+    let io = std::fs::read_to_string(dir.join("io"))
+        .map(|content| crate::utils::procfs::parse_key_values(&content))
+        .unwrap_or_default();
+
+    Some(ProcessInfo {
+        pid,
+        ppid: field(1) as u32,
+        name,
+        uid,
+        cpu_time_ns: (field(11) + field(12)) * 1_000_000_000 / ticks_per_sec,
+        pcpu_time_ns: None,
+        rss_bytes: field(21) * page_size,
+        threads: field(17) as u32,
+        nice: fields[16].parse().unwrap_or(0),
+        state,
+        disk_read_bytes: io.get("read_bytes").copied(),
+        disk_write_bytes: io.get("write_bytes").copied(),
+    })
+}
+
+// Kernel threads have an empty cmdline
+#[cfg(target_os = "linux")]
+fn read_command(pid: u32) -> Option<String> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let command = raw
+        .split(|&b| b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!command.is_empty()).then_some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, cpu_percent: f32, pcpu_percent: Option<f32>) -> ProcessMetrics {
+        ProcessMetrics {
+            pid,
+            cpu_percent,
+            pcpu_percent,
+            ..Default::default()
+        }
+    }
+
+    fn power(ecpu_power: f32, pcpu_power: f32) -> PowerMetrics {
+        PowerMetrics {
+            cpu_power: ecpu_power + pcpu_power,
+            ecpu_power,
+            pcpu_power,
+            ..Default::default()
+        }
+    }
+
+    fn watts(processes: &[ProcessMetrics]) -> Vec<f32> {
+        processes.iter().map(|p| p.energy_watts.unwrap()).collect()
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn splits_cluster_power_by_time_on_each_cluster() {
+        // E time 30/10/0, P time 20/0/60
+        let mut processes = vec![
+            process(1, 50.0, Some(20.0)),
+            process(2, 10.0, Some(0.0)),
+            process(3, 60.0, Some(60.0)),
+        ];
+        attribute_energy(&mut processes, &power(0.8, 4.0));
+
+        let w = watts(&processes);
+        assert_close(&w, &[0.6 + 1.0, 0.2, 3.0]);
+        assert!((w.iter().sum::<f32>() - 4.8).abs() < 1e-4);
+    }
+
+    #[test]
+    fn processes_without_a_split_follow_the_known_ratio() {
+        // Known processes spend 1/4 of their time on P-cores, so process 2 is 30 E + 10 P
+        let mut processes = vec![process(1, 40.0, Some(10.0)), process(2, 40.0, None)];
+        attribute_energy(&mut processes, &power(1.0, 2.0));
+
+        let w = watts(&processes);
+        assert_close(&w, &[1.5, 1.5]);
+        assert!((w.iter().sum::<f32>() - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn shares_total_power_by_cpu_time_without_cluster_power() {
+        let mut processes = vec![process(1, 75.0, Some(75.0)), process(2, 25.0, None)];
+        let power = PowerMetrics {
+            cpu_power: 2.0,
+            ..Default::default()
+        };
+        attribute_energy(&mut processes, &power);
+
+        assert_close(&watts(&processes), &[1.5, 0.5]);
+    }
+
+    #[test]
+    fn parses_filter_terms() {
+        assert!(matches!(parse_term("user:Root"), FilterTerm::User(u) if u == "root"));
+        assert!(matches!(parse_term("u:www"), FilterTerm::User(u) if u == "www"));
+        assert!(matches!(parse_term("name:Safari"), FilterTerm::Name(n) if n == "safari"));
+        assert!(matches!(parse_term("state:R"), FilterTerm::State(s) if s == "r"));
+        assert!(matches!(parse_term("pid:42"), FilterTerm::Pid(42)));
+        assert!(matches!(
+            parse_term("cpu>10"),
+            FilterTerm::Compare(FilterField::Cpu, Ordering::Greater, v, false) if v == 10.0
+        ));
+        assert!(matches!(
+            parse_term("cpu<=2.5"),
+            FilterTerm::Compare(FilterField::Cpu, Ordering::Less, v, true) if v == 2.5
+        ));
+        assert!(matches!(
+            parse_term("mem>500M"),
+            FilterTerm::Compare(FilterField::Memory, Ordering::Greater, v, false)
+                if v == 500.0 * 1024.0 * 1024.0
+        ));
+        assert!(matches!(
+            parse_term("threads=8"),
+            FilterTerm::Compare(FilterField::Threads, Ordering::Equal, v, true) if v == 8.0
+        ));
+        assert!(matches!(parse_term("Chrome"), FilterTerm::Text(t) if t == "chrome"));
+    }
+
+    #[test]
+    fn malformed_terms_fall_back_to_text() {
+        for term in [
+            "pid:abc", "cpu>", "cpu>lots", "mem>5X", "bogus>1", "=3", "pid:-1",
+        ] {
+            assert!(
+                matches!(parse_term(term), FilterTerm::Text(ref t) if *t == term.to_lowercase()),
+                "{} should be a text term",
+                term
+            );
+        }
+        assert!(ProcessFilter::parse("   ").is_empty());
+    }
+
+    #[test]
+    fn every_term_must_match() {
+        let process = ProcessMetrics {
+            pid: 42,
+            name: "postgres".to_string(),
+            command: "/usr/bin/postgres -D /var/lib/pg".to_string(),
+            user: "postgres".to_string(),
+            cpu_percent: 12.0,
+            rss_bytes: 600 * 1024 * 1024,
+            threads: 8,
+            state: "running".to_string(),
+            ..Default::default()
+        };
+        let matches = |expr: &str| ProcessFilter::parse(expr).matches(&process);
+
+        assert!(matches(""));
+        assert!(matches("user:postgres cpu>10 mem>500M"));
+        assert!(matches("var/lib threads>=8 pid:42"));
+        assert!(matches("state:r name:gres"));
+        assert!(!matches("user:postgres cpu>20"));
+        assert!(!matches("threads>8"));
+        assert!(!matches("pid:43"));
+        assert!(!matches("mysql"));
+    }
+
+    // State letter and nice value from /proc/<pid>/stat, polled until `done` accepts them
+    #[cfg(target_os = "linux")]
+    fn wait_for_stat(pid: u32, done: impl Fn(char, i32) -> bool) -> (char, i32) {
+        let mut last = (' ', 0);
+        for _ in 0..100 {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+            let fields: Vec<&str> = stat[stat.rfind(')').unwrap() + 2..].split(' ').collect();
+            last = (
+                fields[0].chars().next().unwrap(),
+                fields[16].parse().unwrap(),
+            );
+            if done(last.0, last.1) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        last
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn actions_signal_and_renice_a_child() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+
+        ProcessAction::Stop.apply(pid).unwrap();
+        assert_eq!(wait_for_stat(pid, |state, _| state == 'T').0, 'T');
+        ProcessAction::Continue.apply(pid).unwrap();
+        assert_eq!(wait_for_stat(pid, |state, _| state == 'S').0, 'S');
+        // Raising the nice value needs no privileges
+        ProcessAction::Renice(5).apply(pid).unwrap();
+        assert_eq!(wait_for_stat(pid, |_, nice| nice == 5).1, 5);
+        ProcessAction::Terminate.apply(pid).unwrap();
+
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+        assert!(ProcessAction::Terminate.apply(0).is_err());
+    }
+}
@@ -0,0 +1,550 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::metrics::chip_profile::ChipProfile;
+use crate::metrics::cpu::CpuCluster;
+use crate::metrics::error::{AtopError, Subsystem};
+use crate::metrics::ioreport_sample::FreqTables;
+use crate::metrics::memory::{MemoryBreakdown, MemoryMetrics, MemoryPressure};
+use crate::metrics::performance::ClusterSample;
+use crate::metrics::{CpuMetrics, PerformanceSample, PowerMetrics};
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// A value over time, `t` in seconds since the start of the run
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Waveform {
+    Constant {
+        value: f64,
+    },
+    Sine {
+        base: f64,
+        amplitude: f64,
+        period_secs: f64,
+    },
+    // Sawtooth from `from` to `to`, restarting every period
+    Ramp {
+        from: f64,
+        to: f64,
+        period_secs: f64,
+    },
+    // `peak` for `width_secs` at the start of every period, `base` otherwise
+    Spikes {
+        base: f64,
+        peak: f64,
+        every_secs: f64,
+        width_secs: f64,
+    },
+    // Uniform in base ± amplitude, the same for a given seed and time
+    Noise {
+        base: f64,
+        amplitude: f64,
+        seed: u64,
+    },
+}
+
+impl Waveform {
+    pub fn value_at(&self, t: f64) -> f64 {
+        match *self {
+            Waveform::Constant { value } => value,
+            Waveform::Sine {
+                base,
+                amplitude,
+                period_secs,
+            } => {
+                if period_secs <= 0.0 {
+                    return base;
+                }
+                base + amplitude * (t / period_secs * std::f64::consts::TAU).sin()
+            }
+            Waveform::Ramp {
+                from,
+                to,
+                period_secs,
+            } => {
+                if period_secs <= 0.0 {
+                    return from;
+                }
+                from + (to - from) * (t.rem_euclid(period_secs) / period_secs)
+            }
+            Waveform::Spikes {
+                base,
+                peak,
+                every_secs,
+                width_secs,
+            } => {
+                if every_secs > 0.0 && t.rem_euclid(every_secs) < width_secs {
+                    peak
+                } else {
+                    base
+                }
+            }
+            Waveform::Noise {
+                base,
+                amplitude,
+                seed,
+            } => {
+                // Keyed by millisecond so the value doesn't depend on how often we sample
+                let hash = splitmix64(seed ^ splitmix64((t * 1000.0).round() as u64));
+                let unit = (hash >> 11) as f64 / (1u64 << 53) as f64;
+                base + amplitude * (unit * 2.0 - 1.0)
+            }
+        }
+    }
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Machine description and one waveform per generated metric; missing fields keep the demo values
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub chip_name: String,
+    pub ecpu_freqs_mhz: Vec<u32>, // DVFS table, also used for throttle detection
+    pub pcpu_freqs_mhz: Vec<u32>,
+    pub ecpu_cores: u32,
+    pub pcpu_cores: u32,
+    pub ecpu_clusters: u32,
+    pub pcpu_clusters: u32,
+    pub dies: u32,
+    pub ram_total_gb: f64,
+    pub swap_total_gb: f64,
+    pub ram_used_gb: Waveform,
+    pub swap_used_gb: Waveform,
+    pub cpu_power_w: Waveform,
+    pub gpu_power_w: Waveform,
+    pub ane_power_w: Waveform,
+    pub ram_power_w: Waveform,
+    pub ecpu_freq_mhz: Waveform,
+    pub pcpu_freq_mhz: Waveform,
+    pub gpu_freq_mhz: Waveform,
+    pub ecpu_usage_percent: Waveform,
+    pub pcpu_usage_percent: Waveform,
+    pub gpu_usage_percent: Waveform,
+}
+
+impl Scenario {
+    /// Read a scenario from a JSON file; errors are tagged `cpu`, the one subsystem a
+    /// `Sampler` can't start without
+    pub fn load(path: &str) -> Result<Self, AtopError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AtopError::unavailable(Subsystem::Cpu, format!("Failed to read {}: {}", path, e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            AtopError::read(Subsystem::Cpu, format!("Invalid scenario {}: {}", path, e))
+        })
+    }
+}
+
+impl Default for Scenario {
+    // A busy M1 Pro: steady P-core load, a GPU burst every 30 s and slowly growing memory
+    fn default() -> Self {
+        Self {
+            chip_name: "Synthetic M1 Pro".to_string(),
+            ecpu_freqs_mhz: vec![600, 972, 1332, 1704, 2064],
+            pcpu_freqs_mhz: vec![
+                600, 828, 1056, 1296, 1524, 1752, 1980, 2208, 2448, 2676, 2904, 3036, 3132, 3168,
+                3228,
+            ],
+            ecpu_cores: 2,
+            pcpu_cores: 8,
+            ecpu_clusters: 1,
+            pcpu_clusters: 2,
+            dies: 1,
+            ram_total_gb: 16.0,
+            swap_total_gb: 2.0,
+            ram_used_gb: Waveform::Ramp {
+                from: 8.0,
+                to: 14.0,
+                period_secs: 120.0,
+            },
+            swap_used_gb: Waveform::Constant { value: 0.5 },
+            cpu_power_w: Waveform::Sine {
+                base: 6.0,
+                amplitude: 4.0,
+                period_secs: 20.0,
+            },
+            gpu_power_w: Waveform::Spikes {
+                base: 0.3,
+                peak: 9.0,
+                every_secs: 30.0,
+                width_secs: 6.0,
+            },
+            ane_power_w: Waveform::Constant { value: 0.0 },
+            ram_power_w: Waveform::Noise {
+                base: 0.6,
+                amplitude: 0.2,
+                seed: 1,
+            },
+            ecpu_freq_mhz: Waveform::Noise {
+                base: 1500.0,
+                amplitude: 400.0,
+                seed: 2,
+            },
+            pcpu_freq_mhz: Waveform::Sine {
+                base: 2400.0,
+                amplitude: 800.0,
+                period_secs: 20.0,
+            },
+            gpu_freq_mhz: Waveform::Spikes {
+                base: 390.0,
+                peak: 1296.0,
+                every_secs: 30.0,
+                width_secs: 6.0,
+            },
+            ecpu_usage_percent: Waveform::Noise {
+                base: 35.0,
+                amplitude: 20.0,
+                seed: 3,
+            },
+            pcpu_usage_percent: Waveform::Sine {
+                base: 50.0,
+                amplitude: 40.0,
+                period_secs: 20.0,
+            },
+            gpu_usage_percent: Waveform::Spikes {
+                base: 3.0,
+                peak: 95.0,
+                every_secs: 30.0,
+                width_secs: 6.0,
+            },
+        }
+    }
+}
+
+/// Memory, power and performance generated from a scenario instead of the OS
+#[derive(Debug, Default, Clone)]
+pub struct SyntheticSample {
+    pub memory: MemoryMetrics,
+    pub power: PowerMetrics,
+    pub performance: PerformanceSample,
+}
+
+/// Generates samples on a virtual clock, so a run is identical however fast it goes
+pub struct SyntheticSampler {
+    scenario: Scenario,
+    elapsed_secs: f64,
+}
+
+impl SyntheticSampler {
+    pub fn new(scenario: Scenario) -> Self {
+        Self {
+            scenario,
+            elapsed_secs: 0.0,
+        }
+    }
+
+    /// The scenario's clusters, laid out the same way as a chip profile would
+    fn clusters(&self) -> Vec<CpuCluster> {
+        let s = &self.scenario;
+        let profile = ChipProfile {
+            ecpu_clusters: s.ecpu_clusters,
+            pcpu_clusters: s.pcpu_clusters,
+            dies: s.dies,
+            ..Default::default()
+        };
+        let tables = FreqTables {
+            ecpu_mhz: s.ecpu_freqs_mhz.clone(),
+            pcpu_mhz: s.pcpu_freqs_mhz.clone(),
+            ..Default::default()
+        };
+        profile.clusters(s.ecpu_cores, s.pcpu_cores, &tables)
+    }
+
+    /// The machine the scenario describes
+    pub fn cpu_metrics(&self) -> CpuMetrics {
+        let s = &self.scenario;
+        let cores = s.ecpu_cores + s.pcpu_cores;
+        CpuMetrics {
+            physical_cores: cores,
+            logical_cores: cores,
+            cpu_brand: s.chip_name.clone(),
+            cpu_frequency_mhz: s.pcpu_freqs_mhz.last().copied().unwrap_or(0) as u64,
+            chip_name: Some(s.chip_name.clone()),
+            ecpu_cores: Some(s.ecpu_cores),
+            pcpu_cores: Some(s.pcpu_cores),
+            ecpu_freqs_mhz: Some(s.ecpu_freqs_mhz.clone()),
+            pcpu_freqs_mhz: Some(s.pcpu_freqs_mhz.clone()),
+            clusters: self.clusters(),
+        }
+    }
+
+    /// Advance the clock by `interval` and return the sample at the new time
+    pub fn sample(&mut self, interval: Duration) -> SyntheticSample {
+        self.elapsed_secs += interval.as_secs_f64();
+        self.sample_at(self.elapsed_secs)
+    }
+
+    pub fn sample_at(&self, t: f64) -> SyntheticSample {
+        let s = &self.scenario;
+        let watts = |w: &Waveform| w.value_at(t).max(0.0) as f32;
+        let mhz = |w: &Waveform| w.value_at(t).max(0.0) as u32;
+        // PerformanceSample holds utilization as a 0.0 - 1.0 ratio
+        let ratio = |w: &Waveform| (w.value_at(t) / 100.0).clamp(0.0, 1.0) as f32;
+
+        let ram_total = (s.ram_total_gb.max(0.0) * GB) as u64;
+        let swap_total = (s.swap_total_gb.max(0.0) * GB) as u64;
+        let ram_usage = ((s.ram_used_gb.value_at(t).max(0.0) * GB) as u64).min(ram_total);
+        let swap_usage = ((s.swap_used_gb.value_at(t).max(0.0) * GB) as u64).min(swap_total);
+        let used_ratio = ram_usage as f64 / ram_total.max(1) as f64;
+        let memory = MemoryMetrics {
+            total: ram_total + swap_total,
+            ram_total,
+            ram_usage,
+            swap_total,
+            swap_usage,
+            // Fixed proportions, enough for the breakdown bar to look plausible
+            breakdown: MemoryBreakdown {
+                active: ram_usage / 2,
+                wired: ram_usage / 4,
+                compressed: ram_usage / 8,
+                inactive: ram_usage - ram_usage / 2 - ram_usage / 4 - ram_usage / 8,
+                free: ram_total - ram_usage,
+                ..Default::default()
+            },
+            pressure: if used_ratio >= 0.95 {
+                MemoryPressure::Critical
+            } else if used_ratio >= 0.85 {
+                MemoryPressure::Warning
+            } else {
+                MemoryPressure::Normal
+            },
+            ..Default::default()
+        };
+
+        let mut performance = PerformanceSample {
+            gpu_usage: (mhz(&s.gpu_freq_mhz), ratio(&s.gpu_usage_percent)),
+            ..Default::default()
+        };
+
+        // Clusters after the first of each kind run the same waveforms a few seconds behind
+        let (mut ecpu, mut pcpu) = ((0.0, 0.0, 0), (0.0, 0.0, 0));
+        for cluster in self.clusters() {
+            let index: u32 = cluster.id[1..].parse().unwrap_or(0);
+            let shifted = t - 7.0 * index as f64;
+            let (freq, usage, totals) = if cluster.efficiency {
+                (&s.ecpu_freq_mhz, &s.ecpu_usage_percent, &mut ecpu)
+            } else {
+                (&s.pcpu_freq_mhz, &s.pcpu_usage_percent, &mut pcpu)
+            };
+            let freq_mhz = freq.value_at(shifted).max(0.0) as u32;
+            let utilization = (usage.value_at(shifted) / 100.0).clamp(0.0, 1.0) as f32;
+            if cluster.cores > 0 {
+                totals.0 += freq_mhz as f64 * cluster.cores as f64;
+                totals.1 += utilization as f64 * cluster.cores as f64;
+                totals.2 += cluster.cores;
+            }
+            performance.clusters.push(ClusterSample {
+                id: cluster.id,
+                freq_mhz,
+                utilization,
+                active: utilization,
+            });
+        }
+
+        // The per-kind figures are core-weighted averages over the clusters, as on hardware
+        let average = |(freq, util, cores): (f64, f64, u32)| {
+            let cores = cores.max(1) as f64;
+            ((freq / cores) as u32, (util / cores) as f32)
+        };
+        performance.ecpu_usage = average(ecpu);
+        performance.pcpu_usage = average(pcpu);
+        performance.ecpu_active = performance.ecpu_usage.1;
+        performance.pcpu_active = performance.pcpu_usage.1;
+
+        // Split CPU power by busy cores, an E-core drawing about a quarter of a P-core
+        let cpu_power = watts(&s.cpu_power_w);
+        let ecpu_weight = 0.25 * s.ecpu_cores as f32 * performance.ecpu_active;
+        let pcpu_weight = s.pcpu_cores as f32 * performance.pcpu_active;
+        let ecpu_share = if ecpu_weight + pcpu_weight > 0.0 {
+            ecpu_weight / (ecpu_weight + pcpu_weight)
+        } else {
+            0.0
+        };
+        let mut power = PowerMetrics {
+            cpu_power,
+            ecpu_power: cpu_power * ecpu_share,
+            pcpu_power: cpu_power * (1.0 - ecpu_share),
+            gpu_power: watts(&s.gpu_power_w),
+            ane_power: watts(&s.ane_power_w),
+            ram_power: watts(&s.ram_power_w),
+            ..Default::default()
+        };
+        power.all_power = power.cpu_power + power.gpu_power + power.ane_power;
+        power.sys_power = power.all_power;
+
+        SyntheticSample {
+            memory,
+            power,
+            performance,
+        }
+    }
+}
+
+impl Default for SyntheticSampler {
+    fn default() -> Self {
+        Self::new(Scenario::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn waveforms_follow_their_shape() {
+        assert_eq!(Waveform::Constant { value: 3.0 }.value_at(42.0), 3.0);
+
+        let sine = |period_secs| Waveform::Sine {
+            base: 10.0,
+            amplitude: 5.0,
+            period_secs,
+        };
+        assert!(close(sine(20.0).value_at(0.0), 10.0));
+        assert!(close(sine(20.0).value_at(5.0), 15.0));
+        assert!(close(sine(20.0).value_at(15.0), 5.0));
+
+        let ramp = |period_secs| Waveform::Ramp {
+            from: 0.0,
+            to: 100.0,
+            period_secs,
+        };
+        assert!(close(ramp(10.0).value_at(5.0), 50.0));
+        // Sawtooth: back to `from` at every period, also before the start
+        assert!(close(ramp(10.0).value_at(10.0), 0.0));
+        assert!(close(ramp(10.0).value_at(-2.5), 75.0));
+
+        let spikes = |every_secs| Waveform::Spikes {
+            base: 1.0,
+            peak: 9.0,
+            every_secs,
+            width_secs: 2.0,
+        };
+        assert_eq!(spikes(30.0).value_at(31.0), 9.0);
+        assert_eq!(spikes(30.0).value_at(33.0), 1.0);
+
+        // A period of zero or less can't repeat, so the shape stays at its starting value
+        for period in [0.0, -5.0] {
+            assert_eq!(sine(period).value_at(7.0), 10.0);
+            assert_eq!(ramp(period).value_at(7.0), 0.0);
+            assert_eq!(spikes(period).value_at(0.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn noise_repeats_for_a_seed_and_time() {
+        let noise = |seed| Waveform::Noise {
+            base: 50.0,
+            amplitude: 10.0,
+            seed,
+        };
+        let values: Vec<f64> = (0..100)
+            .map(|ms| noise(7).value_at(ms as f64 / 10.0))
+            .collect();
+        assert!(values.iter().all(|v| (40.0..=60.0).contains(v)));
+        assert_eq!(
+            values,
+            (0..100)
+                .map(|ms| noise(7).value_at(ms as f64 / 10.0))
+                .collect::<Vec<_>>()
+        );
+        // Keyed by millisecond, and the seed changes the sequence
+        assert_eq!(noise(7).value_at(1.0), noise(7).value_at(1.0001));
+        assert_ne!(noise(7).value_at(1.0), noise(8).value_at(1.0));
+    }
+
+    #[test]
+    fn scenario_json_keeps_defaults_for_missing_fields() {
+        let scenario: Scenario = serde_json::from_str(
+            r#"{"chip_name": "Test", "pcpu_usage_percent": {"shape": "constant", "value": 80}}"#,
+        )
+        .unwrap();
+        let default = Scenario::default();
+        assert_eq!(scenario.chip_name, "Test");
+        assert_eq!(scenario.pcpu_usage_percent.value_at(3.0), 80.0);
+        assert_eq!(
+            (scenario.ecpu_cores, scenario.pcpu_clusters),
+            (default.ecpu_cores, default.pcpu_clusters)
+        );
+        assert_eq!(scenario.pcpu_freqs_mhz, default.pcpu_freqs_mhz);
+        assert_eq!(
+            scenario.gpu_power_w.value_at(1.0),
+            default.gpu_power_w.value_at(1.0)
+        );
+
+        assert!(
+            serde_json::from_str::<Scenario>(r#"{"ram_used_gb": {"shape": "square"}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn memory_stays_within_the_totals() {
+        let sampler = SyntheticSampler::new(Scenario {
+            ram_total_gb: 8.0,
+            ram_used_gb: Waveform::Ramp {
+                from: 4.0,
+                to: 12.0,
+                period_secs: 10.0,
+            },
+            swap_used_gb: Waveform::Constant { value: -1.0 },
+            ..Default::default()
+        });
+        for t in 0..10 {
+            let memory = sampler.sample_at(t as f64).memory;
+            assert!(memory.ram_usage <= memory.ram_total);
+            assert_eq!(memory.swap_usage, 0);
+            let b = &memory.breakdown;
+            assert_eq!(
+                b.active + b.wired + b.compressed + b.inactive + b.free,
+                memory.ram_total
+            );
+        }
+        // Asking for more than there is fills RAM and is critical
+        let full = sampler.sample_at(9.0).memory;
+        assert_eq!(full.ram_usage, full.ram_total);
+        assert!(matches!(full.pressure, MemoryPressure::Critical));
+    }
+
+    #[test]
+    fn kind_figures_are_core_weighted_cluster_averages() {
+        let ramp = |from, to| Waveform::Ramp {
+            from,
+            to,
+            period_secs: 100.0,
+        };
+        // P0 gets 3 cores and P1 2; P1 runs 7 s behind P0
+        let sampler = SyntheticSampler::new(Scenario {
+            pcpu_cores: 5,
+            pcpu_clusters: 2,
+            pcpu_freq_mhz: ramp(1000.0, 2000.0),
+            pcpu_usage_percent: ramp(0.0, 100.0),
+            ..Default::default()
+        });
+        let performance = sampler.sample_at(50.0).performance;
+
+        let p: Vec<_> = performance
+            .clusters
+            .iter()
+            .filter(|c| c.id.starts_with('P'))
+            .collect();
+        assert_eq!((p[0].freq_mhz, p[1].freq_mhz), (1500, 1430));
+        assert!(close(p[0].utilization as f64, 0.50) && close(p[1].utilization as f64, 0.43));
+
+        assert_eq!(performance.pcpu_usage.0, (1500 * 3 + 1430 * 2) / 5);
+        assert!(close(
+            performance.pcpu_usage.1 as f64,
+            (0.50 * 3.0 + 0.43 * 2.0) / 5.0
+        ));
+        assert_eq!(performance.pcpu_active, performance.pcpu_usage.1);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::metrics::cpu::CpuMetrics;
+use crate::metrics::performance::PerformanceSample;
+use serde::Serialize;
+
+// A cluster counts as busy when its cores spend at least this share of the
+// sample window in an active (non-idle) DVFS state.
+const BUSY_ACTIVE_RATIO: f32 = 0.8;
+
+// A busy cluster is considered frequency-capped when its averaged frequency
+// stays below this fraction of the highest frequency in its DVFS table.
+const CAPPED_FREQ_RATIO: f32 = 0.85;
+
+// The cap has to persist at least this long before the cluster is flagged, so
+// a single ramp-up sample does not mark a run as throttled.
+const MIN_THROTTLE_MS: u64 = 2000;
+
+/// Per-cluster throttling state for one sample.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ClusterThrottle {
+    pub cluster: String,
+    pub throttled: bool,
+    pub duration_ms: u64, // continuous time the cluster has been capped
+    pub freq_mhz: u32,
+    pub max_freq_mhz: u32,
+    pub active_ratio: f32, // 0.0 - 1.0
+}
+
+/// Throttling totals for a whole run.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ClusterThrottleSummary {
+    pub cluster: String,
+    pub throttled_ms: u64,        // total time flagged as throttled
+    pub longest_throttle_ms: u64, // longest continuous throttled stretch
+    pub events: u32,              // number of distinct throttled stretches
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ThrottleSummary {
+    pub throttled: bool,
+    pub clusters: Vec<ClusterThrottleSummary>,
+}
+
+#[derive(Debug, Default)]
+struct ClusterState {
+    capped_ms: u64,
+    flagged: bool,
+    summary: ClusterThrottleSummary,
+}
+
+impl ClusterState {
+    fn new(cluster: &str) -> Self {
+        Self {
+            summary: ClusterThrottleSummary {
+                cluster: cluster.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn update(
+        &mut self,
+        usage: (u32, f32),
+        active_ratio: f32,
+        freqs: &[u32],
+        elapsed_ms: u64,
+    ) -> ClusterThrottle {
+        let max_freq_mhz = freqs.iter().copied().max().unwrap_or(0);
+        let (freq_mhz, _) = usage;
+
+        let capped = max_freq_mhz > 0
+            && active_ratio >= BUSY_ACTIVE_RATIO
+            && (freq_mhz as f32) < max_freq_mhz as f32 * CAPPED_FREQ_RATIO;
+
+        if capped {
+            self.capped_ms += elapsed_ms;
+        } else {
+            self.capped_ms = 0;
+        }
+
+        let throttled = self.capped_ms >= MIN_THROTTLE_MS;
+        if throttled {
+            if !self.flagged {
+                // Count the grace period towards the throttled time as well
+                self.summary.events += 1;
+                self.summary.throttled_ms += self.capped_ms;
+            } else {
+                self.summary.throttled_ms += elapsed_ms;
+            }
+            self.summary.longest_throttle_ms = self.summary.longest_throttle_ms.max(self.capped_ms);
+        }
+        self.flagged = throttled;
+
+        ClusterThrottle {
+            cluster: self.summary.cluster.clone(),
+            throttled,
+            duration_ms: if throttled { self.capped_ms } else { 0 },
+            freq_mhz,
+            max_freq_mhz,
+            active_ratio,
+        }
+    }
+}
+
+/// Tracks sustained frequency caps on busy CPU clusters across samples.
+#[derive(Debug)]
+pub struct ThrottleDetector {
+    ecpu: ClusterState,
+    pcpu: ClusterState,
+}
+
+impl Default for ThrottleDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThrottleDetector {
+    pub fn new() -> Self {
+        Self {
+            ecpu: ClusterState::new("ECPU"),
+            pcpu: ClusterState::new("PCPU"),
+        }
+    }
+
+    /// Feed one performance sample covering `elapsed_ms` of wall time
+    pub fn update(
+        &mut self,
+        cpu: &CpuMetrics,
+        perf: &PerformanceSample,
+        elapsed_ms: u64,
+    ) -> Vec<ClusterThrottle> {
+        let mut clusters = Vec::new();
+
+        if let Some(ref freqs) = cpu.ecpu_freqs_mhz {
+            clusters.push(
+                self.ecpu
+                    .update(perf.ecpu_usage, perf.ecpu_active, freqs, elapsed_ms),
+            );
+        }
+        if let Some(ref freqs) = cpu.pcpu_freqs_mhz {
+            clusters.push(
+                self.pcpu
+                    .update(perf.pcpu_usage, perf.pcpu_active, freqs, elapsed_ms),
+            );
+        }
+
+        clusters
+    }
+
+    pub fn summary(&self) -> ThrottleSummary {
+        let clusters = vec![self.ecpu.summary.clone(), self.pcpu.summary.clone()];
+        ThrottleSummary {
+            throttled: clusters.iter().any(|c| c.events > 0),
+            clusters,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu() -> CpuMetrics {
+        CpuMetrics {
+            ecpu_freqs_mhz: Some(vec![600, 1000, 2000]),
+            pcpu_freqs_mhz: Some(vec![600, 2000, 4000]),
+            ..Default::default()
+        }
+    }
+
+    // P-cluster at `pcpu_mhz` with the given active share; the E-cluster stays idle
+    fn sample(pcpu_mhz: u32, pcpu_active: f32) -> PerformanceSample {
+        PerformanceSample {
+            ecpu_usage: (600, 0.1),
+            pcpu_usage: (pcpu_mhz, pcpu_active),
+            ecpu_active: 0.1,
+            pcpu_active,
+            ..Default::default()
+        }
+    }
+
+    fn pcpu(clusters: &[ClusterThrottle]) -> &ClusterThrottle {
+        clusters.iter().find(|c| c.cluster == "PCPU").unwrap()
+    }
+
+    #[test]
+    fn flags_a_cap_only_after_it_is_held() {
+        let cpu = cpu();
+        let mut detector = ThrottleDetector::new();
+
+        // Busy and below 85% of 4000 MHz, but not for 2 s yet
+        for _ in 0..3 {
+            let clusters = detector.update(&cpu, &sample(3000, 0.9), 500);
+            assert!(!pcpu(&clusters).throttled);
+        }
+        let clusters = detector.update(&cpu, &sample(3000, 0.9), 500);
+        assert!(pcpu(&clusters).throttled);
+        assert_eq!(pcpu(&clusters).duration_ms, 2000);
+        assert!(!clusters[0].throttled);
+
+        let clusters = detector.update(&cpu, &sample(3000, 0.9), 500);
+        assert_eq!(pcpu(&clusters).duration_ms, 2500);
+
+        let summary = detector.summary();
+        assert!(summary.throttled);
+        assert_eq!(summary.clusters[1].events, 1);
+        assert_eq!(summary.clusters[1].throttled_ms, 2500);
+        assert_eq!(summary.clusters[1].longest_throttle_ms, 2500);
+    }
+
+    #[test]
+    fn resets_when_frequency_recovers_or_load_drops() {
+        let cpu = cpu();
+        let mut detector = ThrottleDetector::new();
+        for _ in 0..4 {
+            detector.update(&cpu, &sample(3000, 0.9), 500);
+        }
+
+        // 3400 MHz is exactly 85% of the table max, which no longer counts as capped
+        let clusters = detector.update(&cpu, &sample(3400, 0.9), 500);
+        assert!(!pcpu(&clusters).throttled);
+        assert_eq!(pcpu(&clusters).duration_ms, 0);
+
+        // The hold starts over: 1.5 s capped is not enough again
+        for _ in 0..3 {
+            let clusters = detector.update(&cpu, &sample(3000, 0.9), 500);
+            assert!(!pcpu(&clusters).throttled);
+        }
+
+        // A low frequency on a mostly idle cluster is not a cap
+        for _ in 0..6 {
+            let clusters = detector.update(&cpu, &sample(600, 0.5), 500);
+            assert!(!pcpu(&clusters).throttled);
+        }
+        assert_eq!(detector.summary().clusters[1].events, 1);
+    }
+}
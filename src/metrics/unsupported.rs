@@ -0,0 +1,48 @@
+// Stand-ins for the IOReport collectors on platforms without IOReport.
+// Construction always fails, so callers fall back to their "not available" paths; the types
+// have no values, so their methods can't be called.
+use crate::metrics::error::{AtopError, Subsystem};
+use crate::metrics::ioreport_sample::{ChannelDump, IOReportSample};
+use crate::metrics::performance::PerformanceSample;
+use crate::metrics::power::PowerMetrics;
+
+const UNSUPPORTED: &str = "IOReport is only available on macOS";
+
+pub enum IOReport {}
+
+impl IOReport {
+    pub fn new(_groups: Vec<(&str, Option<&str>)>) -> Result<Self, AtopError> {
+        Err(AtopError::Unsupported {
+            subsystem: Subsystem::Power,
+        })
+    }
+
+    pub fn sample_power(&self, _duration_ms: u64) -> Result<IOReportSample, AtopError> {
+        match *self {}
+    }
+}
+
+pub enum IOReportPerf {}
+
+impl IOReportPerf {
+    pub fn new() -> Result<Self, AtopError> {
+        Err(AtopError::Unsupported {
+            subsystem: Subsystem::Performance,
+        })
+    }
+
+    pub fn get_sample(&self, _duration_ms: u64) -> PerformanceSample {
+        match *self {}
+    }
+}
+
+pub fn get_power_metrics_from_sample(
+    ioreport: &IOReport,
+    _interval_ms: u64,
+) -> Result<PowerMetrics, AtopError> {
+    match *ioreport {}
+}
+
+pub fn capture_channel_dump(_interval_ms: u64) -> Result<ChannelDump, Box<dyn std::error::Error>> {
+    Err(UNSUPPORTED.into())
+}
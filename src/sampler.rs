@@ -0,0 +1,329 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::metrics::{
+    self, AtopError, BatteryMetrics, CgroupMetrics, CgroupSampler, ClusterSample, ClusterThrottle,
+    CpuMetrics, CpuTimeMetrics, CpuTimeSampler, DiskMetrics, DiskSampler, FilesystemMetrics,
+    IOReport, IOReportPerf, InterfaceFilter, LoadMetrics, LoadSampler, MemoryMetrics,
+    MemorySampler, NetworkMetrics, NetworkSampler, PowerMetrics, PressureMetrics, ProcessMetrics,
+    ProcessSampler, Scenario, Subsystem, SyntheticSampler, ThrottleDetector, ThrottleSummary,
+    available,
+};
+
+/// What a `Sampler` collects and how
+#[derive(Debug, Clone)]
+pub struct SamplerOptions {
+    /// Length of each sample; rates and power are averaged over it
+    pub interval: Duration,
+    /// Subsystems to collect; CPU and memory are always collected
+    pub subsystems: Vec<Subsystem>,
+    pub interface_filter: InterfaceFilter,
+    pub cgroup_root: String,
+    pub fs_warn_percent: f32,
+    /// Generate memory, power and frequency data from a scenario instead of reading the system
+    pub scenario: Option<Scenario>,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(1000),
+            subsystems: Subsystem::ALL.to_vec(),
+            interface_filter: InterfaceFilter::default(),
+            cgroup_root: metrics::DEFAULT_CGROUP_ROOT.to_string(),
+            fs_warn_percent: metrics::DEFAULT_FS_WARN_PERCENT,
+            scenario: None,
+        }
+    }
+}
+
+/// One sample of every selected subsystem, the document `atop --json` prints
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemMetrics {
+    pub memory: MemoryMetrics,
+    pub cpu: CpuMetrics,
+    pub load: Option<LoadMetrics>,
+    pub cpu_time: Option<CpuTimeMetrics>,
+    pub power: Option<PowerMetrics>,
+    pub ecpu_usage: Option<(u32, f32)>, // (MHz, utilization 0-1)
+    pub pcpu_usage: Option<(u32, f32)>,
+    pub gpu_usage: Option<(u32, f32)>,
+    pub clusters: Option<Vec<ClusterSample>>,
+    pub throttling: Option<Vec<ClusterThrottle>>,
+    pub battery: Option<BatteryMetrics>,
+    pub disks: Option<Vec<DiskMetrics>>,
+    pub network: Option<Vec<NetworkMetrics>>,
+    pub processes: Option<Vec<ProcessMetrics>>,
+    pub cgroups: Option<Vec<CgroupMetrics>>, // Linux cgroup v2 only
+    pub pressure: Option<PressureMetrics>,
+    pub filesystems: Option<Vec<FilesystemMetrics>>,
+    pub unavailable: Vec<AtopError>, // why the selected subsystems that are None have no data
+    pub unix_time: u64,
+}
+
+/// Collects `SystemMetrics`, keeping IOReport subscriptions and rate baselines between samples
+///
+/// Construct it once and call `sample()` repeatedly (or iterate `samples()`); each call blocks
+/// for the configured interval.
+pub struct Sampler {
+    options: SamplerOptions,
+    cpu_metrics: CpuMetrics,
+    perf_monitor: Option<IOReportPerf>,
+    power_monitor: Option<IOReport>,
+    throttle_detector: ThrottleDetector,
+    memory_sampler: MemorySampler,
+    load_sampler: Option<LoadSampler>,
+    cpu_time_sampler: Option<CpuTimeSampler>,
+    disk_sampler: Option<DiskSampler>,
+    network_sampler: Option<NetworkSampler>,
+    process_sampler: Option<ProcessSampler>,
+    cgroup_sampler: Option<CgroupSampler>,
+    synthetic: Option<SyntheticSampler>, // replaces every other source when set
+    setup_errors: Vec<AtopError>,        // IOReport subscriptions that failed in new()
+}
+
+impl Sampler {
+    /// Every subsystem, one-second samples
+    pub fn new() -> Result<Self, AtopError> {
+        Self::with_options(SamplerOptions::default())
+    }
+
+    /// Fails only when the CPU can't be described; other subsystems that can't be opened are
+    /// reported in `SystemMetrics::unavailable`
+    pub fn with_options(options: SamplerOptions) -> Result<Self, AtopError> {
+        let enabled = |subsystem| options.subsystems.contains(&subsystem);
+        let synthetic = options.scenario.clone().map(SyntheticSampler::new);
+        let mut setup_errors = Vec::new();
+        let (cpu_metrics, perf_monitor, power_monitor) = match synthetic {
+            Some(ref synthetic) => (synthetic.cpu_metrics(), None, None),
+            None => (
+                metrics::get_cpu_metrics()?,
+                enabled(Subsystem::Performance)
+                    .then(IOReportPerf::new)
+                    .and_then(|r| available(r, &mut setup_errors)),
+                // Create IOReport instance once and reuse it
+                enabled(Subsystem::Power)
+                    .then(|| IOReport::new(vec![("Energy Model", None)]))
+                    .and_then(|r| available(r, &mut setup_errors)),
+            ),
+        };
+
+        // Rate samplers take their baseline now, so the first sample covers one interval
+        Ok(Self {
+            cpu_metrics,
+            perf_monitor,
+            power_monitor,
+            throttle_detector: ThrottleDetector::new(),
+            memory_sampler: MemorySampler::new(),
+            load_sampler: enabled(Subsystem::Load).then(LoadSampler::new),
+            cpu_time_sampler: enabled(Subsystem::CpuTime).then(CpuTimeSampler::new),
+            disk_sampler: enabled(Subsystem::Disk).then(DiskSampler::new),
+            network_sampler: enabled(Subsystem::Network)
+                .then(|| NetworkSampler::new(options.interface_filter.clone())),
+            process_sampler: enabled(Subsystem::Process).then(ProcessSampler::new),
+            cgroup_sampler: enabled(Subsystem::Cgroup)
+                .then(|| CgroupSampler::new(&options.cgroup_root)),
+            synthetic,
+            setup_errors,
+            options,
+        })
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.options.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.options.interval = interval;
+    }
+
+    /// Static CPU description read in `with_options`
+    pub fn cpu_metrics(&self) -> &CpuMetrics {
+        &self.cpu_metrics
+    }
+
+    /// Frequency caps seen over every sample so far
+    pub fn throttle_summary(&self) -> ThrottleSummary {
+        self.throttle_detector.summary()
+    }
+
+    /// Endless iterator over `sample()`
+    pub fn samples(&mut self) -> Samples<'_> {
+        Samples { sampler: self }
+    }
+
+    /// Block for one interval and return what happened during it
+    pub fn sample(&mut self) -> Result<SystemMetrics, AtopError> {
+        if self.synthetic.is_some() {
+            return Ok(self.sample_synthetic());
+        }
+        let start = Instant::now();
+        let interval_ms = self.options.interval.as_millis() as u64;
+        let mut unavailable = self.setup_errors.clone();
+
+        // Get power metrics using cached IOReport instance
+        let mut power_metrics = self.power_monitor.as_ref().and_then(|m| {
+            available(
+                metrics::get_power_metrics_from_sample(m, interval_ms),
+                &mut unavailable,
+            )
+        });
+
+        // Get performance metrics using cached monitor
+        let perf_sample = self
+            .perf_monitor
+            .as_ref()
+            .map(|monitor| monitor.get_sample(interval_ms));
+
+        // Without IOReport nothing above waits for the interval, so pace the loop here
+        if self.power_monitor.is_none() && self.perf_monitor.is_none() {
+            std::thread::sleep(self.options.interval);
+        }
+
+        // Memory, load, disk, network and process rates cover the time since the previous sample
+        let memory = self.memory_sampler.sample()?;
+        let load = self
+            .load_sampler
+            .as_mut()
+            .and_then(|s| available(s.sample(), &mut unavailable));
+        let cpu_time = self
+            .cpu_time_sampler
+            .as_mut()
+            .and_then(|s| available(s.sample(), &mut unavailable));
+        let disks = self
+            .disk_sampler
+            .as_mut()
+            .and_then(|s| available(s.sample(), &mut unavailable));
+        let network = self
+            .network_sampler
+            .as_mut()
+            .and_then(|s| available(s.sample(), &mut unavailable));
+        let mut processes = self
+            .process_sampler
+            .as_mut()
+            .and_then(|s| available(s.sample(), &mut unavailable));
+        let cgroups = self
+            .cgroup_sampler
+            .as_mut()
+            .and_then(|s| available(s.sample(), &mut unavailable));
+        let pressure = self
+            .enabled(Subsystem::Pressure)
+            .then(metrics::get_pressure_metrics)
+            .and_then(|r| available(r, &mut unavailable));
+        let filesystems = self
+            .enabled(Subsystem::Filesystem)
+            .then(|| metrics::get_filesystem_metrics(self.options.fs_warn_percent))
+            .and_then(|r| available(r, &mut unavailable));
+
+        // Battery telemetry gives a measured whole-system power figure when available
+        let battery = self
+            .enabled(Subsystem::Battery)
+            .then(metrics::get_battery_metrics)
+            .and_then(|r| available(r, &mut unavailable))
+            .flatten();
+        if let Some(ref mut power) = power_metrics {
+            power.apply_system_power(battery.as_ref());
+        }
+
+        // Split CPU power across processes by their time on each cluster
+        if let (Some(procs), Some(power)) = (&mut processes, &power_metrics) {
+            metrics::attribute_energy(procs, power);
+        }
+
+        // Track sustained frequency caps across samples
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        let throttling = perf_sample.as_ref().map(|p| {
+            self.throttle_detector
+                .update(&self.cpu_metrics, p, elapsed_ms)
+        });
+
+        Ok(SystemMetrics {
+            memory,
+            cpu: self.cpu_metrics.clone(),
+            load,
+            cpu_time,
+            power: power_metrics,
+            ecpu_usage: perf_sample.as_ref().map(|p| p.ecpu_usage),
+            pcpu_usage: perf_sample.as_ref().map(|p| p.pcpu_usage),
+            gpu_usage: perf_sample.as_ref().map(|p| p.gpu_usage),
+            clusters: perf_sample.as_ref().map(|p| p.clusters.clone()),
+            throttling,
+            battery,
+            disks,
+            network,
+            processes,
+            cgroups,
+            pressure,
+            filesystems,
+            unavailable,
+            unix_time: unix_time(),
+        })
+    }
+
+    fn enabled(&self, subsystem: Subsystem) -> bool {
+        self.options.subsystems.contains(&subsystem)
+    }
+
+    // Memory, power and frequencies from the scenario; subsystems it doesn't model are left out
+    fn sample_synthetic(&mut self) -> SystemMetrics {
+        std::thread::sleep(self.options.interval);
+        let sample = self
+            .synthetic
+            .as_mut()
+            .expect("synthetic sampler")
+            .sample(self.options.interval);
+        let performance = self
+            .enabled(Subsystem::Performance)
+            .then_some(sample.performance);
+        let throttling = performance.as_ref().map(|p| {
+            self.throttle_detector.update(
+                &self.cpu_metrics,
+                p,
+                self.options.interval.as_millis() as u64,
+            )
+        });
+
+        SystemMetrics {
+            memory: sample.memory,
+            cpu: self.cpu_metrics.clone(),
+            load: None,
+            cpu_time: None,
+            power: self.enabled(Subsystem::Power).then_some(sample.power),
+            ecpu_usage: performance.as_ref().map(|p| p.ecpu_usage),
+            pcpu_usage: performance.as_ref().map(|p| p.pcpu_usage),
+            gpu_usage: performance.as_ref().map(|p| p.gpu_usage),
+            clusters: performance.map(|p| p.clusters),
+            throttling,
+            battery: None,
+            disks: None,
+            network: None,
+            processes: None,
+            cgroups: None,
+            pressure: None,
+            filesystems: None,
+            unavailable: Vec::new(),
+            unix_time: unix_time(),
+        }
+    }
+}
+
+/// Iterator returned by `Sampler::samples`; each `next()` blocks for one interval
+pub struct Samples<'a> {
+    sampler: &'a mut Sampler,
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Result<SystemMetrics, AtopError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.sampler.sample())
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,405 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvError, RecvTimeoutError, SendError, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::metrics::{AtopError, CpuMetrics, Subsystem};
+use crate::sampler::{Sampler, SamplerOptions, SystemMetrics};
+
+/// What a full subscription does with the next value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    DropOldest, // keep the freshest values; a slow reader skips ahead
+    DropNewest, // keep what's queued; new values are lost until the reader catches up
+    Block,      // wait for the reader; a reader that stops reading stalls the publisher
+}
+
+struct Queue<T> {
+    items: VecDeque<T>,
+    subscribed: bool, // Subscription still alive
+    published: bool,  // Publisher still alive and not closed
+}
+
+struct Shared<T> {
+    queue: Mutex<Queue<T>>,
+    changed: Condvar,
+    capacity: usize,
+    policy: Backpressure,
+}
+
+/// Bounded single-reader channel with a backpressure policy; std's `sync_channel` can only
+/// block or refuse the new value, never evict the oldest one
+pub fn bounded<T>(capacity: usize, policy: Backpressure) -> (Publisher<T>, Subscription<T>) {
+    let capacity = capacity.max(1);
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue {
+            items: VecDeque::with_capacity(capacity),
+            subscribed: true,
+            published: true,
+        }),
+        changed: Condvar::new(),
+        capacity,
+        policy,
+    });
+    let publisher = Publisher {
+        shared: Arc::clone(&shared),
+    };
+    (publisher, Subscription { shared })
+}
+
+/// Sending half of `bounded`
+pub struct Publisher<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Publisher<T> {
+    /// Queue a value under the channel's policy; fails once the subscription is dropped or the
+    /// publisher is closed
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let shared = &self.shared;
+        let mut queue = shared.queue.lock().unwrap();
+        loop {
+            if !queue.subscribed || !queue.published {
+                return Err(SendError(value));
+            }
+            if queue.items.len() < shared.capacity {
+                break;
+            }
+            match shared.policy {
+                Backpressure::DropOldest => {
+                    queue.items.pop_front();
+                    break;
+                }
+                Backpressure::DropNewest => return Ok(()),
+                Backpressure::Block => queue = shared.changed.wait(queue).unwrap(),
+            }
+        }
+        queue.items.push_back(value);
+        shared.changed.notify_all();
+        Ok(())
+    }
+
+    pub fn is_subscribed(&self) -> bool {
+        self.shared.queue.lock().unwrap().subscribed
+    }
+
+    /// End the stream: the reader drains what's queued, then sees a disconnect, and a blocked
+    /// `send` gives up
+    pub fn close(&self) {
+        self.shared.queue.lock().unwrap().published = false;
+        self.shared.changed.notify_all();
+    }
+}
+
+impl<T> Drop for Publisher<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Receiving half of `bounded`; the methods mirror `std::sync::mpsc::Receiver`
+pub struct Subscription<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Subscription<T> {
+    /// Wait for the next value; fails once the queue is empty and the publisher is gone
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        loop {
+            if let Some(value) = queue.items.pop_front() {
+                self.shared.changed.notify_all();
+                return Ok(value);
+            }
+            if !queue.published {
+                return Err(RecvError);
+            }
+            queue = self.shared.changed.wait(queue).unwrap();
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        match queue.items.pop_front() {
+            Some(value) => {
+                self.shared.changed.notify_all();
+                Ok(value)
+            }
+            None if queue.published => Err(TryRecvError::Empty),
+            None => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut queue = self.shared.queue.lock().unwrap();
+        loop {
+            if let Some(value) = queue.items.pop_front() {
+                self.shared.changed.notify_all();
+                return Ok(value);
+            }
+            if !queue.published {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            queue = self
+                .shared
+                .changed
+                .wait_timeout(queue, remaining)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Blocking iterator that ends when the publisher is gone
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.recv().ok())
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().subscribed = false;
+        self.shared.changed.notify_all();
+    }
+}
+
+type SampleResult = Result<SystemMetrics, AtopError>;
+type Callback = Box<dyn FnMut(&SampleResult) + Send>;
+
+/// Runs a `Sampler` on its own thread and pushes every sample to subscribers and callbacks
+///
+/// Dropping it stops the thread after the sample in progress and ends every subscription.
+pub struct BackgroundSampler {
+    cpu_metrics: CpuMetrics,
+    interval: Arc<RwLock<Duration>>,
+    publishers: Arc<Mutex<Vec<Arc<Publisher<SampleResult>>>>>,
+    callbacks: Arc<Mutex<Vec<Callback>>>,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundSampler {
+    /// Start sampling right away; samples taken before the first subscriber are discarded
+    ///
+    /// The `Sampler` is built on the sampling thread (IOReport handles can't move between
+    /// threads), and its error is returned here.
+    pub fn spawn(options: SamplerOptions) -> Result<Self, AtopError> {
+        let interval = Arc::new(RwLock::new(options.interval));
+        let publishers: Arc<Mutex<Vec<Arc<Publisher<SampleResult>>>>> = Arc::default();
+        let callbacks: Arc<Mutex<Vec<Callback>>> = Arc::default();
+        let running = Arc::new(AtomicBool::new(true));
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let handle = {
+            let interval = Arc::clone(&interval);
+            let publishers = Arc::clone(&publishers);
+            let callbacks = Arc::clone(&callbacks);
+            let running = Arc::clone(&running);
+            thread::spawn(move || {
+                let mut sampler = match Sampler::with_options(options) {
+                    Ok(sampler) => {
+                        let _ = ready_tx.send(Ok(sampler.cpu_metrics().clone()));
+                        sampler
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                while running.load(Ordering::Acquire) {
+                    sampler.set_interval(*interval.read().unwrap());
+                    let result = sampler.sample();
+                    if !running.load(Ordering::Acquire) {
+                        break;
+                    }
+
+                    for callback in callbacks.lock().unwrap().iter_mut() {
+                        callback(&result);
+                    }
+                    // Send outside the lock so a blocking subscriber doesn't hold up subscribe()
+                    let targets = publishers.lock().unwrap().clone();
+                    for publisher in &targets {
+                        let _ = publisher.send(result.clone());
+                    }
+                    publishers.lock().unwrap().retain(|p| p.is_subscribed());
+                }
+            })
+        };
+
+        let ready = ready_rx
+            .recv()
+            .unwrap_or_else(|_| Err(AtopError::read(Subsystem::Cpu, "sampling thread panicked")));
+        let cpu_metrics = match ready {
+            Ok(cpu_metrics) => cpu_metrics,
+            Err(e) => {
+                let _ = handle.join();
+                return Err(e);
+            }
+        };
+        Ok(Self {
+            cpu_metrics,
+            interval,
+            publishers,
+            callbacks,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// Receive every sample from now on, queueing at most `capacity` under `policy`
+    pub fn subscribe(&self, capacity: usize, policy: Backpressure) -> Subscription<SampleResult> {
+        let (publisher, subscription) = bounded(capacity, policy);
+        self.publishers.lock().unwrap().push(Arc::new(publisher));
+        subscription
+    }
+
+    /// Call `callback` on the sampling thread with every sample from now on; a slow callback
+    /// delays the next sample
+    pub fn on_sample(&self, callback: impl FnMut(&SampleResult) + Send + 'static) {
+        self.callbacks.lock().unwrap().push(Box::new(callback));
+    }
+
+    /// Static CPU description the sampler read at startup
+    pub fn cpu_metrics(&self) -> &CpuMetrics {
+        &self.cpu_metrics
+    }
+
+    pub fn interval(&self) -> Duration {
+        *self.interval.read().unwrap()
+    }
+
+    /// Takes effect from the next sample
+    pub fn set_interval(&self, interval: Duration) {
+        *self.interval.write().unwrap() = interval;
+    }
+
+    /// Same as dropping it, spelled out at the call site
+    pub fn stop(self) {}
+}
+
+impl Drop for BackgroundSampler {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        // Wake a send blocked on a full subscription so the thread can see the flag
+        for publisher in self.publishers.lock().unwrap().iter() {
+            publisher.close();
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(subscription: &Subscription<u32>) -> Vec<u32> {
+        std::iter::from_fn(|| subscription.try_recv().ok()).collect()
+    }
+
+    #[test]
+    fn full_queues_follow_the_policy() {
+        let (publisher, subscription) = bounded(2, Backpressure::DropOldest);
+        for i in 0..5 {
+            publisher.send(i).unwrap();
+        }
+        assert_eq!(drain(&subscription), vec![3, 4]);
+
+        let (publisher, subscription) = bounded(2, Backpressure::DropNewest);
+        for i in 0..5 {
+            publisher.send(i).unwrap();
+        }
+        assert_eq!(drain(&subscription), vec![0, 1]);
+    }
+
+    #[test]
+    fn blocked_sends_wait_for_the_reader() {
+        let (publisher, subscription) = bounded(1, Backpressure::Block);
+        let writer = thread::spawn(move || {
+            for i in 0..3 {
+                publisher.send(i).unwrap();
+            }
+        });
+        assert_eq!(subscription.iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn dropping_either_half_disconnects() {
+        let (publisher, subscription) = bounded(1, Backpressure::Block);
+        publisher.send(1).unwrap();
+        drop(publisher);
+        assert_eq!(subscription.try_recv(), Ok(1));
+        assert_eq!(subscription.try_recv(), Err(TryRecvError::Disconnected));
+
+        let (publisher, subscription) = bounded(1, Backpressure::Block);
+        publisher.send(1).unwrap();
+        drop(subscription);
+        assert_eq!(publisher.send(2), Err(SendError(2)));
+    }
+
+    fn scenario_sampler() -> BackgroundSampler {
+        BackgroundSampler::spawn(SamplerOptions {
+            interval: Duration::from_millis(20),
+            scenario: Some(crate::metrics::Scenario::default()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn background_sampler_feeds_subscribers_and_callbacks() {
+        let sampler = scenario_sampler();
+        let updates = sampler.subscribe(4, Backpressure::DropOldest);
+        let (count_tx, count_rx) = mpsc::channel();
+        sampler.on_sample(move |result| {
+            let _ = count_tx.send(result.is_ok());
+        });
+
+        let timeout = Duration::from_secs(5);
+        assert!(updates.recv_timeout(timeout).unwrap().is_ok());
+        assert_eq!(count_rx.recv_timeout(timeout), Ok(true));
+
+        sampler.set_interval(Duration::from_millis(10));
+        assert_eq!(sampler.interval(), Duration::from_millis(10));
+        assert!(updates.recv_timeout(timeout).unwrap().is_ok());
+
+        sampler.stop();
+        // Whatever was queued is still readable, then the stream ends
+        while updates.try_recv().is_ok() {}
+        assert_eq!(updates.try_recv().err(), Some(TryRecvError::Disconnected));
+        // and the callbacks are dropped with the thread
+        while count_rx.try_recv().is_ok() {}
+        assert_eq!(count_rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn dropping_the_sampler_unblocks_a_stalled_subscriber() {
+        let sampler = scenario_sampler();
+        let stalled = sampler.subscribe(1, Backpressure::Block);
+        let (count_tx, count_rx) = mpsc::channel();
+        sampler.on_sample(move |_| {
+            let _ = count_tx.send(());
+        });
+        // The first sample fills the queue and the thread blocks sending the second
+        for _ in 0..2 {
+            count_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::spawn(move || {
+            drop(sampler);
+            let _ = done_tx.send(());
+        });
+        assert_eq!(done_rx.recv_timeout(Duration::from_secs(5)), Ok(()));
+        assert!(stalled.try_recv().unwrap().is_ok());
+        assert_eq!(stalled.try_recv().err(), Some(TryRecvError::Disconnected));
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::metrics::CgroupMetrics;
+
+/// A visible line of the cgroup table
+pub struct CgroupRow<'a> {
+    pub group: &'a CgroupMetrics,
+    pub depth: usize,
+    pub has_children: bool,
+    pub expanded: bool,
+}
+
+/// Expansion and selection state of the cgroup tree
+pub struct CgroupView {
+    expanded: HashSet<String>, // only the root is expanded initially
+    selected: Option<String>,  // selected path, so it survives re-sorting
+}
+
+impl CgroupView {
+    pub fn new() -> Self {
+        Self {
+            expanded: HashSet::from(["/".to_string()]),
+            selected: None,
+        }
+    }
+
+    pub fn set_expanded(&mut self, expanded: bool) {
+        if let Some(ref path) = self.selected {
+            if expanded {
+                self.expanded.insert(path.clone());
+            } else {
+                self.expanded.remove(path);
+            }
+        }
+    }
+
+    pub fn toggle_expanded(&mut self) {
+        if let Some(ref path) = self.selected {
+            let expanded = self.expanded.contains(path);
+            self.set_expanded(!expanded);
+        }
+    }
+
+    /// Move the selection by `delta` rows, clamped to the visible rows
+    pub fn move_selection(&mut self, groups: &[CgroupMetrics], delta: isize) {
+        let rows = self.rows(groups);
+        if rows.is_empty() {
+            self.selected = None;
+            return;
+        }
+        let index = match self.selected_index(&rows) {
+            Some(index) => (index as isize + delta).clamp(0, rows.len() as isize - 1) as usize,
+            None => 0,
+        };
+        self.selected = Some(rows[index].group.path.clone());
+    }
+
+    pub fn selected_index(&self, rows: &[CgroupRow]) -> Option<usize> {
+        let path = self.selected.as_ref()?;
+        rows.iter().position(|row| row.group.path == *path)
+    }
+
+    /// Visible rows of the hierarchy, busiest siblings first
+    pub fn rows<'a>(&self, groups: &'a [CgroupMetrics]) -> Vec<CgroupRow<'a>> {
+        let mut children: HashMap<&str, Vec<&CgroupMetrics>> = HashMap::new();
+        let mut roots = Vec::new();
+        for group in groups {
+            match parent_path(&group.path) {
+                Some(parent) => children.entry(parent).or_default().push(group),
+                None => roots.push(group),
+            }
+        }
+        for list in children.values_mut() {
+            list.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        }
+
+        let mut rows = Vec::new();
+        let mut stack: Vec<(&CgroupMetrics, usize)> =
+            roots.iter().rev().map(|&root| (root, 0)).collect();
+        while let Some((group, depth)) = stack.pop() {
+            let kids = children.get(group.path.as_str());
+            let expanded = self.expanded.contains(&group.path);
+            rows.push(CgroupRow {
+                group,
+                depth,
+                has_children: kids.is_some(),
+                expanded,
+            });
+            if expanded && let Some(kids) = kids {
+                stack.extend(kids.iter().rev().map(|&kid| (kid, depth + 1)));
+            }
+        }
+
+        rows
+    }
+}
+
+impl Default for CgroupView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// "/a/b" -> "/a", "/a" -> "/", "/" -> None
+fn parent_path(path: &str) -> Option<&str> {
+    if path == "/" {
+        return None;
+    }
+    match path.rsplit_once('/') {
+        Some(("", _)) => Some("/"),
+        Some((parent, _)) => Some(parent),
+        None => None,
+    }
+}
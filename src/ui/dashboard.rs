@@ -1,33 +1,120 @@
 use std::collections::VecDeque;
 use std::io;
-use std::sync::mpsc::{self, Receiver};
-use std::sync::{Arc, RwLock};
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{
-    Frame,
+    Frame, Terminal,
+    backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Row, Table},
+    widgets::{Block, Borders, Clear, LineGauge, Paragraph, Row, Table, TableState},
 };
 
-use crate::metrics::{self, ioreport_perf, memory};
+use crate::metrics::{self, memory};
+use crate::ui::cgroup_view::CgroupView;
+use crate::ui::process_view::{ProcessSort, ProcessView};
+use crate::ui::source::{CollectorSource, MetricData, MetricSource};
 use crate::ui::time_graph::TimeGraph;
 
-enum MetricEvent {
-    Update(MetricData),
+const MAX_HISTORY: usize = 128;
+
+// How long action results stay in the footer
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Graph panels are this tall when the terminal has room and shrink down to the minimum
+const GRAPH_HEIGHT: u16 = 8;
+const MIN_GRAPH_HEIGHT: u16 = 5;
+
+// Rows the process (or cgroup) table keeps before any panel is shown
+const MIN_TABLE_HEIGHT: u16 = 12;
+
+// Panels above the process table, top to bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Panel {
+    CpuInfo,
+    CpuGraph,
+    Memory,
+    Frequency,
+    Power,
+    Disk,
+    Network,
+    Performance,
 }
 
-struct MetricData {
-    memory: memory::MemoryMetrics,
-    power: Option<metrics::PowerMetrics>,
-    performance: Option<ioreport_perf::PerformanceSample>,
+impl Panel {
+    const ALL: [Panel; 8] = [
+        Panel::CpuInfo,
+        Panel::CpuGraph,
+        Panel::Memory,
+        Panel::Frequency,
+        Panel::Power,
+        Panel::Disk,
+        Panel::Network,
+        Panel::Performance,
+    ];
+
+    // Collapsed first when the terminal is too short, lower panels before the core graphs
+    const COLLAPSE_ORDER: [Panel; 8] = [
+        Panel::Network,
+        Panel::Disk,
+        Panel::Performance,
+        Panel::Frequency,
+        Panel::Power,
+        Panel::Memory,
+        Panel::CpuInfo,
+        Panel::CpuGraph,
+    ];
+
+    fn is_graph(self) -> bool {
+        !matches!(self, Panel::CpuInfo | Panel::Performance)
+    }
+
+    fn min_height(self) -> u16 {
+        if self.is_graph() { MIN_GRAPH_HEIGHT } else { 7 }
+    }
 }
 
-const MAX_HISTORY: usize = 128;
+// Panels that fit above the table in `height` rows, with their heights: panels collapse until
+// the rest fit at their minimum, graphs then grow up to GRAPH_HEIGHT and the table gets the
+// leftover rows
+fn panel_layout(height: u16) -> Vec<(Panel, u16)> {
+    let budget = height.saturating_sub(MIN_TABLE_HEIGHT);
+    let mut shown = Panel::ALL.to_vec();
+    let min_total = |shown: &[Panel]| shown.iter().map(|p| p.min_height()).sum::<u16>();
+    for panel in Panel::COLLAPSE_ORDER {
+        if min_total(&shown) <= budget {
+            break;
+        }
+        shown.retain(|&p| p != panel);
+    }
+
+    let mut layout: Vec<(Panel, u16)> = shown.iter().map(|&p| (p, p.min_height())).collect();
+    let mut spare = budget - min_total(&shown);
+    // One row at a time, top to bottom, so the graphs stay about the same height
+    while spare > 0 {
+        let mut grew = false;
+        for (panel, rows) in layout.iter_mut() {
+            if spare > 0 && panel.is_graph() && *rows < GRAPH_HEIGHT {
+                *rows += 1;
+                spare -= 1;
+                grew = true;
+            }
+        }
+        if !grew {
+            break;
+        }
+    }
+    layout
+}
+
+// Action waiting for confirmation in the modal dialog
+struct PendingAction {
+    action: metrics::ProcessAction,
+    pid: u32,
+    name: String,
+}
 
 struct DashboardState {
     // CPU info (static, doesn't change)
@@ -36,7 +123,27 @@ struct DashboardState {
     // Current values
     current_memory: Option<memory::MemoryMetrics>,
     current_power: Option<metrics::PowerMetrics>,
-    current_performance: Option<ioreport_perf::PerformanceSample>,
+    current_performance: Option<metrics::PerformanceSample>,
+    current_throttling: Vec<metrics::ClusterThrottle>,
+    current_battery: Option<metrics::BatteryMetrics>,
+    current_disks: Option<Vec<metrics::DiskMetrics>>,
+    current_network: Option<Vec<metrics::NetworkMetrics>>,
+    current_processes: Option<Vec<metrics::ProcessMetrics>>,
+    current_cgroups: Option<Vec<metrics::CgroupMetrics>>,
+    current_pressure: Option<metrics::PressureMetrics>,
+    current_load: Option<metrics::LoadMetrics>,
+    current_cpu_time: Option<metrics::CpuTimeMetrics>,
+    current_filesystems: Option<Vec<metrics::FilesystemMetrics>>,
+    current_unavailable: Vec<metrics::AtopError>,
+
+    // Process table sorting, filtering and selection
+    process_view: ProcessView,
+    pending_action: Option<PendingAction>,
+    message: Option<(String, bool, Instant)>, // text, is error, shown at
+
+    // Cgroup table, shown in place of the process table
+    group_view: bool,
+    cgroup_view: CgroupView,
 
     // Historical data for sparklines
     memory_history: VecDeque<u64>,      // RAM usage in bytes
@@ -53,15 +160,55 @@ struct DashboardState {
     pcpu_usage_history: VecDeque<u64>, // P-CPU usage 0-100
     gpu_usage_history: VecDeque<u64>,  // GPU usage 0-100
     cpu_usage_history: VecDeque<u64>,  // Combined CPU usage 0-100
+
+    disk_read_history: VecDeque<u64>, // Disk reads in bytes/s (all devices)
+    disk_write_history: VecDeque<u64>, // Disk writes in bytes/s (all devices)
+
+    net_rx_history: VecDeque<u64>, // Network received bytes/s (filtered interfaces)
+    net_tx_history: VecDeque<u64>, // Network transmitted bytes/s (filtered interfaces)
 }
 
 impl DashboardState {
+    fn throttling(&self, cluster: &str) -> Option<&metrics::ClusterThrottle> {
+        self.current_throttling
+            .iter()
+            .find(|t| t.cluster == cluster && t.throttled)
+    }
+
+    // Placeholder for an empty panel, with the reason when the collector gave one
+    fn not_available(&self, what: &str, subsystem: metrics::Subsystem) -> String {
+        match self
+            .current_unavailable
+            .iter()
+            .find(|e| e.subsystem() == subsystem)
+        {
+            Some(err) => format!("{} not available: {}", what, err.reason()),
+            None => format!("{} not available", what),
+        }
+    }
+
     fn new() -> Self {
         Self {
             cpu_metrics: None,
             current_memory: None,
             current_power: None,
             current_performance: None,
+            current_throttling: Vec::new(),
+            current_battery: None,
+            current_disks: None,
+            current_network: None,
+            current_processes: None,
+            current_cgroups: None,
+            current_pressure: None,
+            current_load: None,
+            current_cpu_time: None,
+            current_filesystems: None,
+            current_unavailable: Vec::new(),
+            process_view: ProcessView::new(),
+            pending_action: None,
+            message: None,
+            group_view: false,
+            cgroup_view: CgroupView::new(),
             memory_history: VecDeque::with_capacity(MAX_HISTORY),
             cpu_power_history: VecDeque::with_capacity(MAX_HISTORY),
             gpu_power_history: VecDeque::with_capacity(MAX_HISTORY),
@@ -74,6 +221,10 @@ impl DashboardState {
             pcpu_usage_history: VecDeque::with_capacity(MAX_HISTORY),
             gpu_usage_history: VecDeque::with_capacity(MAX_HISTORY),
             cpu_usage_history: VecDeque::with_capacity(MAX_HISTORY),
+            disk_read_history: VecDeque::with_capacity(MAX_HISTORY),
+            disk_write_history: VecDeque::with_capacity(MAX_HISTORY),
+            net_rx_history: VecDeque::with_capacity(MAX_HISTORY),
+            net_tx_history: VecDeque::with_capacity(MAX_HISTORY),
         }
     }
 
@@ -82,6 +233,21 @@ impl DashboardState {
         self.current_memory = Some(data.memory.clone());
         self.current_power = data.power.clone();
         self.current_performance = data.performance.clone();
+        self.current_battery = data.battery.clone();
+        self.current_disks = data.disks.clone();
+        self.current_network = data.network.clone();
+        self.current_processes = data.processes.clone();
+        self.current_cgroups = data.cgroups.clone();
+        self.current_pressure = data.pressure.clone();
+        self.current_load = data.load.clone();
+        self.current_cpu_time = data.cpu_time.clone();
+        self.current_filesystems = data.filesystems.clone();
+        self.current_unavailable = data.unavailable.clone();
+
+        // The sampler tracks frequency caps across samples
+        if let Some(ref throttling) = data.throttling {
+            self.current_throttling = throttling.clone();
+        }
 
         // Update memory history
         self.memory_history.push_front(data.memory.ram_usage);
@@ -134,72 +300,58 @@ impl DashboardState {
                 self.gpu_usage_history.pop_back();
                 self.cpu_usage_history.pop_back();
             }
+        } else if let Some(ref cpu_time) = data.cpu_time {
+            // Without IOReport residency, fall back to the tick-based busy share
+            self.cpu_usage_history
+                .push_front(cpu_time.total.busy() as u64);
+            if self.cpu_usage_history.len() > MAX_HISTORY {
+                self.cpu_usage_history.pop_back();
+            }
+        }
+
+        // Update disk history (summed over all devices)
+        if let Some(ref disks) = data.disks {
+            self.disk_read_history
+                .push_front(disks.iter().map(|d| d.read_bytes_per_sec).sum());
+            self.disk_write_history
+                .push_front(disks.iter().map(|d| d.write_bytes_per_sec).sum());
+
+            if self.disk_read_history.len() > MAX_HISTORY {
+                self.disk_read_history.pop_back();
+                self.disk_write_history.pop_back();
+            }
+        }
+
+        // Update network history (summed over filtered interfaces)
+        if let Some(ref network) = data.network {
+            self.net_rx_history
+                .push_front(network.iter().map(|n| n.rx_bytes_per_sec).sum());
+            self.net_tx_history
+                .push_front(network.iter().map(|n| n.tx_bytes_per_sec).sum());
+
+            if self.net_rx_history.len() > MAX_HISTORY {
+                self.net_rx_history.pop_back();
+                self.net_tx_history.pop_back();
+            }
         }
     }
 }
 
-pub struct Dashboard {
-    refresh_interval: Arc<RwLock<Duration>>,
+pub struct Dashboard<S: MetricSource = CollectorSource> {
+    source: S,
     state: DashboardState,
-    metric_receiver: Receiver<MetricEvent>,
 }
 
-impl Dashboard {
-    pub fn new() -> io::Result<Self> {
-        let (tx, rx) = mpsc::sync_channel::<MetricEvent>(10);
-        let refresh_interval = Arc::new(RwLock::new(Duration::from_millis(1000)));
-
-        // Spawn metric collection thread that runs continuously
-        let tx_clone = tx;
-        let interval_clone = Arc::clone(&refresh_interval);
-        thread::spawn(move || {
-            let perf_monitor = ioreport_perf::IOReportPerf::new().ok();
-            // Create IOReport instance once and reuse it
-            let power_monitor = metrics::iokit::IOReport::new(vec![("Energy Model", None)]).ok();
-
-            loop {
-                // Read the current interval from the shared RwLock
-                let interval = *interval_clone.read().unwrap();
-
-                // Collect all metrics in one go
-                let memory = memory::get_memory_metrics().ok();
-                let power = power_monitor.as_ref().and_then(|m| {
-                    metrics::get_power_metrics_from_sample(m, interval.as_millis() as u64).ok()
-                });
-                let performance = perf_monitor
-                    .as_ref()
-                    .map(|m| m.get_sample(interval.as_millis() as u64));
-
-                if let Some(mem) = memory {
-                    // Use try_send to avoid blocking. If channel is full, discard the old message
-                    use std::sync::mpsc::TrySendError;
-                    match tx_clone.try_send(MetricEvent::Update(MetricData {
-                        memory: mem,
-                        power,
-                        performance,
-                    })) {
-                        Ok(_) => {}
-                        Err(TrySendError::Full(_)) => {
-                            // Channel is full, discard this metric update
-                            // The UI will get the next fresh one
-                        }
-                        Err(TrySendError::Disconnected(_)) => {
-                            // Receiver has been dropped, exit thread
-                            break;
-                        }
-                    }
-                }
-
-                // Sleep for the interval duration
-                thread::sleep(interval);
-            }
-        });
+impl<S: MetricSource> Dashboard<S> {
+    pub fn with_source(mut source: S) -> io::Result<Self> {
+        // Get CPU metrics once (they don't change)
+        let cpu_metrics = source
+            .cpu_metrics()
+            .map_err(|e| io::Error::other(format!("{}", e)))?;
+        let mut state = DashboardState::new();
+        state.cpu_metrics = Some(cpu_metrics);
 
-        Ok(Dashboard {
-            refresh_interval,
-            state: DashboardState::new(),
-            metric_receiver: rx,
-        })
+        Ok(Dashboard { source, state })
     }
 
     pub fn run(&mut self) -> io::Result<()> {
@@ -217,12 +369,6 @@ impl Dashboard {
         let backend = ratatui::backend::CrosstermBackend::new(stdout);
         let mut terminal = ratatui::Terminal::new(backend)?;
 
-        // Get CPU metrics once (they don't change)
-        self.state.cpu_metrics = Some(
-            metrics::get_cpu_metrics()
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?,
-        );
-
         // ==============================================================================
         // Main Event Loop
         // ==============================================================================
@@ -242,50 +388,204 @@ impl Dashboard {
         result
     }
 
-    fn event_loop(
-        &mut self,
-        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<io::Stdout>>,
-    ) -> io::Result<()> {
+    fn event_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
         loop {
-            // Draw the dashboard
-            terminal.draw(|f| self.render(f))?;
-
-            // Process all pending metrics from the collection thread
-            while let Ok(MetricEvent::Update(data)) = self.metric_receiver.try_recv() {
-                self.state.update(data);
-            }
+            self.draw(terminal)?;
 
             // Poll for keyboard events with a timeout
             // This timeout controls the UI refresh rate when no events occur
-            if event::poll(Duration::from_millis(50))? {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
-                        match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => break,
-                            KeyCode::Char('+') | KeyCode::Char('=') => {
-                                // Increase refresh interval (slower refresh)
-                                let mut interval = self.refresh_interval.write().unwrap();
-                                let millis = interval.as_millis() as u64;
-                                if millis < 5000 {
-                                    *interval = Duration::from_millis(millis + 100);
-                                }
-                            }
-                            KeyCode::Char('-') => {
-                                // Decrease refresh interval (faster refresh)
-                                let mut interval = self.refresh_interval.write().unwrap();
-                                let millis = interval.as_millis() as u64;
-                                if millis > 100 {
-                                    *interval = Duration::from_millis(millis - 100);
-                                }
-                            }
-                            _ => {}
-                        }
+            if event::poll(Duration::from_millis(50))?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && self.handle_key(key)
+            {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draw the current state, then take in every pending update from the source
+    pub fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        terminal.draw(|f| self.render(f))?;
+
+        while let Some(data) = self.source.poll() {
+            self.state.update(data);
+        }
+
+        Ok(())
+    }
+
+    // Returns true when the dashboard should quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        // The confirmation dialog takes every key while it is open
+        if let Some(pending) = self.state.pending_action.take() {
+            self.state.message = Some(match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    match pending.action.apply(pending.pid) {
+                        Ok(()) => (
+                            format!(
+                                "{}: {} ({}) done",
+                                pending.action.label(),
+                                pending.pid,
+                                pending.name
+                            ),
+                            false,
+                            Instant::now(),
+                        ),
+                        Err(e) => (
+                            format!(
+                                "{} {} ({}) failed: {}",
+                                pending.action.label(),
+                                pending.pid,
+                                pending.name,
+                                e.reason()
+                            ),
+                            true,
+                            Instant::now(),
+                        ),
                     }
                 }
+                _ => ("Cancelled".to_string(), false, Instant::now()),
+            });
+            return false;
+        }
+
+        if self.state.group_view {
+            return self.handle_group_key(key);
+        }
+
+        let view = &mut self.state.process_view;
+        let processes = self.state.current_processes.as_deref().unwrap_or(&[]);
+
+        // While typing a filter every character goes into the expression
+        if view.editing {
+            match key.code {
+                KeyCode::Char(c) => view.push_query(c),
+                KeyCode::Backspace => view.pop_query(),
+                KeyCode::Enter => view.editing = false,
+                KeyCode::Esc => {
+                    view.clear_query();
+                    view.editing = false;
+                }
+                _ => {}
             }
+            return false;
         }
 
-        Ok(())
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc => {
+                // Esc clears an active filter first
+                if view.query().is_empty() {
+                    return true;
+                }
+                view.clear_query();
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.slower_refresh(),
+            KeyCode::Char('-') => self.faster_refresh(),
+            KeyCode::Char('g') => self.state.group_view = true,
+            KeyCode::Char('>') | KeyCode::Char('.') => view.sort = view.sort.next(),
+            KeyCode::Char('<') | KeyCode::Char(',') => view.sort = view.sort.prev(),
+            KeyCode::Char('r') => view.sort_desc = !view.sort_desc,
+            KeyCode::Char('/') => view.editing = true,
+            KeyCode::Char('t') => view.tree = !view.tree,
+            KeyCode::Char('p') => view.toggle_pin(),
+            KeyCode::Char('k') => self.confirm_action(|_| metrics::ProcessAction::Terminate),
+            KeyCode::Char('K') => self.confirm_action(|_| metrics::ProcessAction::Kill),
+            KeyCode::Char('s') => self.confirm_action(|_| metrics::ProcessAction::Stop),
+            KeyCode::Char('c') => self.confirm_action(|_| metrics::ProcessAction::Continue),
+            KeyCode::Char('n') => {
+                self.confirm_action(|p| metrics::ProcessAction::Renice((p.nice + 1).min(19)))
+            }
+            KeyCode::Char('N') => {
+                self.confirm_action(|p| metrics::ProcessAction::Renice((p.nice - 1).max(-20)))
+            }
+            KeyCode::Char(' ') => view.toggle_collapsed(),
+            KeyCode::Left => view.set_collapsed(true),
+            KeyCode::Right => view.set_collapsed(false),
+            KeyCode::Up => view.move_selection(processes, -1),
+            KeyCode::Down => view.move_selection(processes, 1),
+            KeyCode::PageUp => view.move_selection(processes, -10),
+            KeyCode::PageDown => view.move_selection(processes, 10),
+            KeyCode::Home => view.move_selection(processes, isize::MIN / 2),
+            KeyCode::End => view.move_selection(processes, isize::MAX / 2),
+            _ => {}
+        }
+
+        false
+    }
+
+    // Keys while the cgroup table is shown; process actions don't apply there
+    fn handle_group_key(&mut self, key: KeyEvent) -> bool {
+        let view = &mut self.state.cgroup_view;
+        let groups = self.state.current_cgroups.as_deref().unwrap_or(&[]);
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('+') | KeyCode::Char('=') => self.slower_refresh(),
+            KeyCode::Char('-') => self.faster_refresh(),
+            KeyCode::Char('g') => self.state.group_view = false,
+            KeyCode::Char(' ') => view.toggle_expanded(),
+            KeyCode::Left => view.set_expanded(false),
+            KeyCode::Right => view.set_expanded(true),
+            KeyCode::Up => view.move_selection(groups, -1),
+            KeyCode::Down => view.move_selection(groups, 1),
+            KeyCode::PageUp => view.move_selection(groups, -10),
+            KeyCode::PageDown => view.move_selection(groups, 10),
+            KeyCode::Home => view.move_selection(groups, isize::MIN / 2),
+            KeyCode::End => view.move_selection(groups, isize::MAX / 2),
+            _ => {}
+        }
+
+        false
+    }
+
+    fn slower_refresh(&mut self) {
+        let millis = self.source.interval().as_millis() as u64;
+        if millis < 5000 {
+            self.source
+                .set_interval(Duration::from_millis(millis + 100));
+        }
+    }
+
+    fn faster_refresh(&mut self) {
+        let millis = self.source.interval().as_millis() as u64;
+        if millis > 100 {
+            self.source
+                .set_interval(Duration::from_millis(millis - 100));
+        }
+    }
+
+    // Ask for confirmation before acting on the selected process
+    fn confirm_action(
+        &mut self,
+        action: impl Fn(&metrics::ProcessMetrics) -> metrics::ProcessAction,
+    ) {
+        let selected = self.state.process_view.selected().and_then(|pid| {
+            self.state
+                .current_processes
+                .as_ref()?
+                .iter()
+                .find(|p| p.pid == pid)
+        });
+        match selected {
+            Some(process) => {
+                self.state.pending_action = Some(PendingAction {
+                    action: action(process),
+                    pid: process.pid,
+                    name: process.name.clone(),
+                });
+            }
+            None => {
+                self.state.message = Some((
+                    "Select a process first (Up/Down)".to_string(),
+                    true,
+                    Instant::now(),
+                ));
+            }
+        }
     }
 
     fn render(&self, frame: &mut Frame) {
@@ -293,55 +593,137 @@ impl Dashboard {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Min(0),    // Content
-                Constraint::Length(3), // Footer
+                Constraint::Length(4), // Footer
             ])
             .split(frame.area());
 
         // ==============================================================================
         // Main Content Area
         // ==============================================================================
+        let panels = panel_layout(chunks[0].height);
         let content_chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(7), // CPU info text
-                Constraint::Length(8), // CPU Usage Graph
-                Constraint::Length(8), // Memory Graph
-                Constraint::Length(8), // Frequency Graphs
-                Constraint::Length(8), // Power Graphs
-                Constraint::Min(8),    // Performance table
-            ])
+            .constraints(
+                panels
+                    .iter()
+                    .map(|&(_, rows)| Constraint::Length(rows))
+                    .chain([Constraint::Min(0)]), // Process table
+            )
             .split(chunks[0]);
 
-        // CPU info text
-        self.render_cpu_info(frame, content_chunks[0]);
-
-        // CPU Usage Graph
-        self.render_cpu_graph(frame, content_chunks[1]);
-
-        // Memory Graph
-        self.render_memory_info(frame, content_chunks[2]);
-
-        // Frequency Graphs
-        self.render_frequency_graphs(frame, content_chunks[3]);
-
-        // Power Graphs
-        self.render_power_info(frame, content_chunks[4]);
+        for (&(panel, _), &area) in panels.iter().zip(content_chunks.iter()) {
+            match panel {
+                Panel::CpuInfo => self.render_cpu_info(frame, area),
+                Panel::CpuGraph => self.render_cpu_graph(frame, area),
+                Panel::Memory => self.render_memory_info(frame, area),
+                Panel::Frequency => self.render_frequency_graphs(frame, area),
+                Panel::Power => self.render_power_info(frame, area),
+                Panel::Disk => self.render_disk_info(frame, area),
+                Panel::Network => self.render_network_info(frame, area),
+                Panel::Performance => self.render_performance_table(frame, area),
+            }
+        }
 
-        // Performance Table
-        self.render_performance_table(frame, content_chunks[5]);
+        // Process or Cgroup Table
+        let table_area = content_chunks[panels.len()];
+        if self.state.group_view {
+            self.render_cgroup_table(frame, table_area);
+        } else {
+            self.render_process_table(frame, table_area);
+        }
 
         // ==============================================================================
         // Footer with Controls
         // ==============================================================================
-        let footer_text = format!(
-            "Refresh: {:.1}s | [+/-] Adjust Rate | [q/ESC] Quit",
-            self.refresh_interval.read().unwrap().as_secs_f32()
-        );
-        let footer = Paragraph::new(footer_text)
+        let view = &self.state.process_view;
+        let recent_message = self
+            .state
+            .message
+            .as_ref()
+            .filter(|(_, _, at)| at.elapsed() < MESSAGE_TIMEOUT);
+        let footer_lines = if self.state.group_view {
+            vec![
+                Line::from(format!(
+                    "Refresh: {:.1}s | [+/-] Rate | [g] Processes | [q/ESC] Quit",
+                    self.source.interval().as_secs_f32()
+                )),
+                Line::from(
+                    "[Up/Down] Select | [Space] Expand/Collapse | [Left/Right] Collapse/Expand",
+                ),
+            ]
+        } else if view.editing {
+            vec![
+                Line::from(format!("Filter: {}_", view.query())),
+                Line::from("e.g. user:ci cpu>10 mem>500M | [Enter] Apply | [ESC] Clear"),
+            ]
+        } else {
+            let second = match recent_message {
+                Some((text, true, _)) => {
+                    Line::styled(text.clone(), Style::default().fg(Color::Red))
+                }
+                Some((text, false, _)) => {
+                    Line::styled(text.clone(), Style::default().fg(Color::Green))
+                }
+                None => Line::from(
+                    "[Up/Down] Select | [k/K] Term/Kill | [s/c] Stop/Cont | [n/N] Nice +/- | [/] Filter | [t] Tree | [Space] Fold | [p] Pin | [g] Cgroups",
+                ),
+            };
+            vec![
+                Line::from(format!(
+                    "Refresh: {:.1}s | [+/-] Rate | [</>] Sort | [r] Reverse | [q/ESC] Quit",
+                    self.source.interval().as_secs_f32()
+                )),
+                second,
+            ]
+        };
+        let footer = Paragraph::new(footer_lines)
             .block(Block::default().borders(Borders::ALL))
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::DarkGray));
         frame.render_widget(footer, chunks[1]);
+
+        // ==============================================================================
+        // Confirmation Dialog
+        // ==============================================================================
+        if let Some(ref pending) = self.state.pending_action {
+            self.render_confirm_dialog(frame, pending);
+        }
+    }
+
+    fn render_confirm_dialog(&self, frame: &mut Frame, pending: &PendingAction) {
+        // Centered box on top of everything else
+        let screen = frame.area();
+        let width = 56.min(screen.width);
+        let height = 5.min(screen.height);
+        let area = Rect::new(
+            screen.x + (screen.width - width) / 2,
+            screen.y + (screen.height - height) / 2,
+            width,
+            height,
+        );
+
+        let text = vec![
+            Line::from(format!(
+                "{} process {} ({})?",
+                pending.action.label(),
+                pending.pid,
+                pending.name
+            )),
+            Line::from(""),
+            Line::styled(
+                "[y/Enter] Confirm   [any other key] Cancel",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ];
+        let dialog = Paragraph::new(text).alignment(Alignment::Center).block(
+            Block::default()
+                .title(" Confirm ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(dialog, area);
     }
 
     fn render_cpu_info(&self, frame: &mut Frame, area: Rect) {
@@ -365,7 +747,12 @@ impl Dashboard {
                     Line::from(vec![
                         Span::raw("  P/E: "),
                         Span::styled(
-                            format!("{} P-cores, {} E-cores", p, e),
+                            format!(
+                                "{} P-cores, {} E-cores{}",
+                                p,
+                                e,
+                                format_cluster_layout(&cpu.clusters)
+                            ),
                             Style::default().fg(Color::Cyan),
                         ),
                     ])
@@ -379,18 +766,97 @@ impl Dashboard {
                         Style::default().fg(Color::Cyan),
                     ),
                 ]),
+                if let Some(ref battery) = self.state.current_battery {
+                    Line::from(vec![
+                        Span::raw("Battery: "),
+                        Span::styled(
+                            format_battery(battery),
+                            Style::default().fg(if battery.charge_percent < 20.0 {
+                                Color::Red
+                            } else {
+                                Color::Green
+                            }),
+                        ),
+                    ])
+                } else {
+                    Line::from(vec![])
+                },
             ]
         } else {
             vec![Line::from("Loading...")]
         };
 
-        let cpu_block = Paragraph::new(cpu_info).block(
-            Block::default()
-                .title(" CPU ")
-                .borders(Borders::ALL)
-                .style(Style::default().fg(Color::White)),
-        );
-        frame.render_widget(cpu_block, area);
+        let block = Block::default()
+            .title(" CPU ")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Static CPU details on the left, scheduler activity on the right
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(inner);
+        frame.render_widget(Paragraph::new(cpu_info), columns[0]);
+
+        let Some(ref load) = self.state.current_load else {
+            return;
+        };
+        // Load above the core count means tasks are queueing for a CPU
+        let cores = self
+            .state
+            .cpu_metrics
+            .as_ref()
+            .map(|c| c.logical_cores as f32)
+            .unwrap_or(f32::MAX);
+        let load_color = if load.load_1 > cores {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        let rate = |value: Option<f32>| {
+            value
+                .map(|v| format!("{:.0}/s", v))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let tasks = match (load.running_tasks, load.blocked_tasks) {
+            (Some(running), Some(blocked)) => format!("{} running, {} blocked", running, blocked),
+            (Some(running), None) => format!("{} running", running),
+            _ => "-".to_string(),
+        };
+        let load_info = vec![
+            Line::from(vec![
+                Span::raw("Load: "),
+                Span::styled(
+                    format!("{:.2} {:.2} {:.2}", load.load_1, load.load_5, load.load_15),
+                    Style::default().fg(load_color),
+                ),
+            ]),
+            Line::from(vec![
+                Span::raw("Tasks: "),
+                Span::styled(tasks, Style::default().fg(Color::Cyan)),
+            ]),
+            Line::from(vec![
+                Span::raw("Ctx switches: "),
+                Span::styled(
+                    rate(load.context_switches_per_sec),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]),
+            Line::from(vec![
+                Span::raw("Interrupts: "),
+                Span::styled(
+                    rate(load.interrupts_per_sec),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]),
+            Line::from(vec![
+                Span::raw("Forks: "),
+                Span::styled(rate(load.forks_per_sec), Style::default().fg(Color::Cyan)),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(load_info), columns[1]);
     }
 
     fn render_memory_info(&self, frame: &mut Frame, area: Rect) {
@@ -399,6 +865,16 @@ impl Dashboard {
             let used_gb = mem.ram_usage as f64 / 1_073_741_824.0;
             let usage_percent = (mem.ram_usage as f64 / mem.ram_total as f64 * 100.0) as u64;
 
+            // Split area: usage graph, page state breakdown, stall gauges
+            let mem_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(45),
+                    Constraint::Percentage(30),
+                    Constraint::Percentage(25),
+                ])
+                .split(area);
+
             let graph = TimeGraph::new(&self.state.memory_history)
                 .max(mem.ram_total)
                 .style(Style::default().fg(Color::Blue))
@@ -411,7 +887,9 @@ impl Dashboard {
                         .borders(Borders::ALL),
                 );
 
-            frame.render_widget(graph, area);
+            frame.render_widget(graph, mem_chunks[0]);
+            self.render_memory_breakdown(frame, mem_chunks[1], mem);
+            self.render_pressure(frame, mem_chunks[2], mem);
         } else {
             let loading = Paragraph::new("Loading...")
                 .block(Block::default().title(" Memory ").borders(Borders::ALL));
@@ -419,6 +897,147 @@ impl Dashboard {
         }
     }
 
+    fn render_memory_breakdown(&self, frame: &mut Frame, area: Rect, mem: &memory::MemoryMetrics) {
+        let b = &mem.breakdown;
+        let segments = [
+            ("Wired", b.wired, Color::Red),
+            ("Active", b.active, Color::Yellow),
+            ("Compressed", b.compressed, Color::Magenta),
+            ("Inactive", b.inactive, Color::Blue),
+            ("Free", b.free, Color::DarkGray),
+        ];
+
+        // Stacked bar spanning the inner width, one cell per share of total RAM
+        let width = area.width.saturating_sub(2) as u64;
+        let mut bar = Vec::new();
+        let mut filled = 0;
+        for (_, bytes, color) in segments {
+            let cells = (bytes.saturating_mul(width) / mem.ram_total.max(1)).min(width - filled);
+            filled += cells;
+            bar.push(Span::styled(
+                "█".repeat(cells as usize),
+                Style::default().fg(color),
+            ));
+        }
+
+        let mut lines = vec![Line::from(bar)];
+        for pair in segments.chunks(2) {
+            let spans: Vec<Span> = pair
+                .iter()
+                .flat_map(|(label, bytes, color)| {
+                    [
+                        Span::styled("■ ", Style::default().fg(*color)),
+                        Span::raw(format!(
+                            "{:<11}{:>5.1} GB  ",
+                            label,
+                            *bytes as f64 / 1_073_741_824.0
+                        )),
+                    ]
+                })
+                .collect();
+            lines.push(Line::from(spans));
+        }
+        let activity = &mem.activity;
+        lines.push(Line::from(format!(
+            "Pages in/out {:.0}/{:.0}/s  Swap {:.0}/{:.0}/s",
+            activity.pageins_per_sec,
+            activity.pageouts_per_sec,
+            activity.swapins_per_sec,
+            activity.swapouts_per_sec
+        )));
+
+        let pressure_color = match mem.pressure {
+            metrics::MemoryPressure::Normal => Color::Green,
+            metrics::MemoryPressure::Warning => Color::Yellow,
+            metrics::MemoryPressure::Critical => Color::Red,
+        };
+        let title = Line::from(vec![
+            Span::raw(" Pressure: "),
+            Span::styled(
+                format!("{:?} ", mem.pressure),
+                Style::default().fg(pressure_color),
+            ),
+        ]);
+
+        let paragraph =
+            Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL));
+        frame.render_widget(paragraph, area);
+    }
+
+    // High CPU with low stalls is just busy; stalls mean tasks are waiting on the resource
+    fn render_pressure(&self, frame: &mut Frame, area: Rect, mem: &memory::MemoryMetrics) {
+        let block = Block::default()
+            .title(" Stalls (avg10) ")
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 6])
+            .split(inner);
+
+        let level_color = |level| match level {
+            metrics::MemoryPressure::Normal => Color::Green,
+            metrics::MemoryPressure::Warning => Color::Yellow,
+            metrics::MemoryPressure::Critical => Color::Red,
+        };
+
+        let psi = self
+            .state
+            .current_pressure
+            .as_ref()
+            .filter(|p| p.cpu.is_some() || p.memory.is_some() || p.io.is_some());
+        let Some(psi) = psi else {
+            // macOS only exposes the kernel's memory pressure level
+            let level = self
+                .state
+                .current_pressure
+                .as_ref()
+                .map(|p| p.memory_level)
+                .unwrap_or(mem.pressure);
+            let ratio = match level {
+                metrics::MemoryPressure::Normal => 0.1,
+                metrics::MemoryPressure::Warning => 0.6,
+                metrics::MemoryPressure::Critical => 1.0,
+            };
+            let gauge = LineGauge::default()
+                .label(format!("Mem {:<9}", format!("{:?}", level)))
+                .ratio(ratio)
+                .filled_style(Style::default().fg(level_color(level)));
+            frame.render_widget(gauge, rows[0]);
+            frame.render_widget(
+                Paragraph::new("No CPU/IO stall data").style(Style::default().fg(Color::DarkGray)),
+                rows[2],
+            );
+            return;
+        };
+
+        let resources = [("CPU", &psi.cpu), ("Mem", &psi.memory), ("I/O", &psi.io)];
+        for (i, (label, pressure)) in resources.into_iter().enumerate() {
+            let Some(pressure) = pressure else {
+                continue;
+            };
+            let some = pressure.some.avg10;
+            let full = pressure.full.as_ref().map(|f| f.avg10);
+            let gauge = LineGauge::default()
+                .label(format!("{} {:>5.1}%", label, some))
+                .ratio((some as f64 / 100.0).clamp(0.0, 1.0))
+                .filled_style(
+                    Style::default().fg(level_color(metrics::stall_level(&pressure.some))),
+                );
+            frame.render_widget(gauge, rows[i * 2]);
+            let detail = match full {
+                Some(full) => format!("    full {:.1}%  60s {:.1}%", full, pressure.some.avg60),
+                None => format!("    60s {:.1}%", pressure.some.avg60),
+            };
+            frame.render_widget(
+                Paragraph::new(detail).style(Style::default().fg(Color::DarkGray)),
+                rows[i * 2 + 1],
+            );
+        }
+    }
+
     fn render_power_info(&self, frame: &mut Frame, area: Rect) {
         // Split area into 4 sections: Total, CPU, GPU, ANE power
         let power_chunks = Layout::default()
@@ -439,7 +1058,14 @@ impl Dashboard {
                 .style(Style::default().fg(Color::White))
                 .block(
                     Block::default()
-                        .title(format!(" Total: {:.2}W ", power.all_power))
+                        .title(if power.sys_power > power.all_power {
+                            format!(
+                                " Total: {:.2}W (System {:.2}W) ",
+                                power.all_power, power.sys_power
+                            )
+                        } else {
+                            format!(" Total: {:.2}W ", power.all_power)
+                        })
                         .borders(Borders::ALL),
                 );
             frame.render_widget(total_graph, power_chunks[0]);
@@ -477,15 +1103,18 @@ impl Dashboard {
                 );
             frame.render_widget(ane_graph, power_chunks[3]);
         } else {
-            let no_data = Paragraph::new("Power metrics not available")
-                .block(Block::default().title(" Power ").borders(Borders::ALL));
+            let no_data = Paragraph::new(
+                self.state
+                    .not_available("Power metrics", metrics::Subsystem::Power),
+            )
+            .block(Block::default().title(" Power ").borders(Borders::ALL));
             frame.render_widget(no_data, area);
         }
     }
 
     fn render_performance_table(&self, frame: &mut Frame, area: Rect) {
         if let Some(ref perf) = self.state.current_performance {
-            let header = Row::new(vec!["Cluster", "Frequency", "Utilization"])
+            let header = Row::new(vec!["Cluster", "Frequency", "Utilization", "Status"])
                 .style(Style::default().add_modifier(Modifier::BOLD))
                 .bottom_margin(1);
 
@@ -496,10 +1125,26 @@ impl Dashboard {
             let gpu_freq = format!("{} MHz", perf.gpu_usage.0);
             let gpu_util = format!("{:.1}%", perf.gpu_usage.1);
 
+            let status = |cluster: &str| match self.state.throttling(cluster) {
+                Some(t) => format!("THROTTLED {}s", t.duration_ms / 1000),
+                None => String::new(),
+            };
+            let ecpu_status = status("ECPU");
+            let pcpu_status = status("PCPU");
+            let row_style = |cluster: &str| {
+                if self.state.throttling(cluster).is_some() {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                }
+            };
+
             let rows = vec![
-                Row::new(vec!["E-Cluster", &ecpu_freq, &ecpu_util]),
-                Row::new(vec!["P-Cluster", &pcpu_freq, &pcpu_util]),
-                Row::new(vec!["GPU", &gpu_freq, &gpu_util]),
+                Row::new(vec!["E-Cluster", &ecpu_freq, &ecpu_util, &ecpu_status])
+                    .style(row_style("ECPU")),
+                Row::new(vec!["P-Cluster", &pcpu_freq, &pcpu_util, &pcpu_status])
+                    .style(row_style("PCPU")),
+                Row::new(vec!["GPU", &gpu_freq, &gpu_util, ""]),
             ];
 
             let table = Table::new(
@@ -508,6 +1153,7 @@ impl Dashboard {
                     Constraint::Length(10),
                     Constraint::Length(12),
                     Constraint::Length(12),
+                    Constraint::Length(16),
                 ],
             )
             .header(header)
@@ -519,7 +1165,11 @@ impl Dashboard {
 
             frame.render_widget(table, area);
         } else {
-            let no_data = Paragraph::new("Performance metrics not available").block(
+            let no_data = Paragraph::new(
+                self.state
+                    .not_available("Performance metrics", metrics::Subsystem::Performance),
+            )
+            .block(
                 Block::default()
                     .title(" Performance ")
                     .borders(Borders::ALL),
@@ -528,19 +1178,426 @@ impl Dashboard {
         }
     }
 
+    fn render_process_table(&self, frame: &mut Frame, area: Rect) {
+        let Some(ref processes) = self.state.current_processes else {
+            let loading = Paragraph::new("Loading...")
+                .block(Block::default().title(" Processes ").borders(Borders::ALL));
+            frame.render_widget(loading, area);
+            return;
+        };
+
+        let view = &self.state.process_view;
+        let rows = view.rows(processes);
+        let selected = view.selected_index(&rows);
+
+        // Mark the sorted column in the header
+        let header = Row::new(ProcessSort::ALL.iter().map(|&column| {
+            if column == view.sort {
+                let arrow = if view.sort_desc { "▼" } else { "▲" };
+                Span::styled(
+                    format!("{}{}", column.title(), arrow),
+                    Style::default().fg(Color::Cyan),
+                )
+            } else {
+                Span::raw(column.title())
+            }
+        }))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+        let shown = rows.len();
+        let table_rows = rows.iter().map(|row| {
+            let p = row.process;
+            // Tree view indents children and marks foldable nodes
+            let marker = match (row.has_children, row.collapsed) {
+                (true, true) => "▸ ",
+                (true, false) => "▾ ",
+                _ if view.tree => "  ",
+                _ => "",
+            };
+            let name = format!("{}{}{}", "  ".repeat(row.depth), marker, p.name);
+            let style = if row.pinned {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                p.pid.to_string(),
+                name,
+                p.user.clone(),
+                format!("{:.1}", p.cpu_percent),
+                format_bytes(p.rss_bytes),
+                p.threads.to_string(),
+                p.nice.to_string(),
+                p.state.clone(),
+                p.energy_watts
+                    .map(|w| format!("{:.2} W", w))
+                    .unwrap_or_else(|| "-".to_string()),
+                p.disk_read_bytes_per_sec
+                    .map(format_bytes_rate)
+                    .unwrap_or_else(|| "-".to_string()),
+                p.disk_write_bytes_per_sec
+                    .map(format_bytes_rate)
+                    .unwrap_or_else(|| "-".to_string()),
+            ])
+            .style(style)
+        });
+
+        let mut title = format!(" Processes ({}/{}) ", shown, processes.len());
+        if view.tree {
+            title.push_str("[tree] ");
+        }
+        if !view.query().is_empty() {
+            title.push_str(&format!("[filter: {}] ", view.query()));
+        }
+
+        let table = Table::new(
+            table_rows,
+            [
+                Constraint::Length(8),
+                Constraint::Min(16),
+                Constraint::Length(12),
+                Constraint::Length(7),
+                Constraint::Length(10),
+                Constraint::Length(5),
+                Constraint::Length(4),
+                Constraint::Length(9),
+                Constraint::Length(8),
+                Constraint::Length(11),
+                Constraint::Length(11),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::default().title(title).borders(Borders::ALL));
+
+        // Scrolls so the selected row stays in view
+        let mut table_state = TableState::default().with_selected(selected);
+        frame.render_stateful_widget(table, area, &mut table_state);
+    }
+
+    fn render_cgroup_table(&self, frame: &mut Frame, area: Rect) {
+        let Some(ref groups) = self.state.current_cgroups else {
+            let unavailable = Paragraph::new(
+                self.state
+                    .not_available("Cgroups", metrics::Subsystem::Cgroup),
+            )
+            .block(Block::default().title(" Cgroups ").borders(Borders::ALL));
+            frame.render_widget(unavailable, area);
+            return;
+        };
+
+        let view = &self.state.cgroup_view;
+        let rows = view.rows(groups);
+        let selected = view.selected_index(&rows);
+
+        let header = Row::new(vec![
+            "Group", "Procs", "CPU%", "Mem", "Anon", "File", "IO R/s", "IO W/s", "CPU PSI",
+            "Mem PSI",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+        // Pressure as the share of the last 10s with some task stalled
+        let psi = |pressure: &Option<metrics::pressure::Pressure>| {
+            pressure
+                .as_ref()
+                .map(|p| format!("{:.1}%", p.some.avg10))
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        let table_rows = rows.iter().map(|row| {
+            let g = row.group;
+            let marker = match (row.has_children, row.expanded) {
+                (true, false) => "▸ ",
+                (true, true) => "▾ ",
+                _ => "  ",
+            };
+            let name = g
+                .path
+                .rsplit('/')
+                .next()
+                .filter(|n| !n.is_empty())
+                .unwrap_or("/");
+            Row::new(vec![
+                format!("{}{}{}", "  ".repeat(row.depth), marker, name),
+                g.processes.to_string(),
+                format!("{:.1}", g.cpu_percent),
+                format_bytes(g.memory_current),
+                format_bytes(g.memory_anon),
+                format_bytes(g.memory_file),
+                format_bytes_rate(g.io_read_bytes_per_sec),
+                format_bytes_rate(g.io_write_bytes_per_sec),
+                psi(&g.cpu_pressure),
+                psi(&g.memory_pressure),
+            ])
+        });
+
+        let title = format!(" Cgroups ({}/{}) ", rows.len(), groups.len());
+        let table = Table::new(
+            table_rows,
+            [
+                Constraint::Min(24),
+                Constraint::Length(6),
+                Constraint::Length(7),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::default().title(title).borders(Borders::ALL));
+
+        let mut table_state = TableState::default().with_selected(selected);
+        frame.render_stateful_widget(table, area, &mut table_state);
+    }
+
+    fn render_disk_info(&self, frame: &mut Frame, area: Rect) {
+        // Split area into 3 sections: Read, Write throughput, filesystem usage
+        let disk_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(35),
+                Constraint::Percentage(35),
+                Constraint::Percentage(30),
+            ])
+            .split(area);
+        self.render_filesystems(frame, disk_chunks[2]);
+
+        if let Some(ref disks) = self.state.current_disks {
+            let read: u64 = disks.iter().map(|d| d.read_bytes_per_sec).sum();
+            let write: u64 = disks.iter().map(|d| d.write_bytes_per_sec).sum();
+            let read_iops: f32 = disks.iter().map(|d| d.read_iops).sum();
+            let write_iops: f32 = disks.iter().map(|d| d.write_iops).sum();
+            let busy = disks.iter().map(|d| d.busy_percent).fold(0.0, f32::max);
+
+            // Scale both graphs to the busiest recent value, at least 1 MB/s
+            let max_rate = self
+                .state
+                .disk_read_history
+                .iter()
+                .chain(self.state.disk_write_history.iter())
+                .copied()
+                .max()
+                .unwrap_or(0)
+                .max(1024 * 1024);
+
+            let read_graph = TimeGraph::new(&self.state.disk_read_history)
+                .max(max_rate)
+                .style(Style::default().fg(Color::Green))
+                .block(
+                    Block::default()
+                        .title(format!(
+                            " Disk Read: {} ({:.0} IOPS) ",
+                            format_bytes_rate(read),
+                            read_iops
+                        ))
+                        .borders(Borders::ALL),
+                );
+            frame.render_widget(read_graph, disk_chunks[0]);
+
+            let write_graph = TimeGraph::new(&self.state.disk_write_history)
+                .max(max_rate)
+                .style(Style::default().fg(Color::Red))
+                .block(
+                    Block::default()
+                        .title(format!(
+                            " Disk Write: {} ({:.0} IOPS) | Busy {:.0}% ",
+                            format_bytes_rate(write),
+                            write_iops,
+                            busy
+                        ))
+                        .borders(Borders::ALL),
+                );
+            frame.render_widget(write_graph, disk_chunks[1]);
+        } else {
+            let no_data = Paragraph::new(
+                self.state
+                    .not_available("Disk metrics", metrics::Subsystem::Disk),
+            )
+            .block(Block::default().title(" Disk ").borders(Borders::ALL));
+            let graphs_area = disk_chunks[0].union(disk_chunks[1]);
+            frame.render_widget(no_data, graphs_area);
+        }
+    }
+
+    // fullest volumes first, flagged ones in red
+    fn render_filesystems(&self, frame: &mut Frame, area: Rect) {
+        let Some(ref filesystems) = self.state.current_filesystems else {
+            let no_data = Paragraph::new(
+                self.state
+                    .not_available("Filesystems", metrics::Subsystem::Filesystem),
+            )
+            .block(
+                Block::default()
+                    .title(" Filesystems ")
+                    .borders(Borders::ALL),
+            );
+            frame.render_widget(no_data, area);
+            return;
+        };
+
+        let mut sorted: Vec<&metrics::FilesystemMetrics> = filesystems.iter().collect();
+        sorted.sort_by(|a, b| {
+            let fullest =
+                |f: &metrics::FilesystemMetrics| f.used_percent.max(f.inodes_used_percent);
+            fullest(b).total_cmp(&fullest(a))
+        });
+
+        let warnings = filesystems.iter().filter(|f| f.warning).count();
+        let title = if warnings > 0 {
+            Line::from(vec![
+                Span::raw(" Filesystems "),
+                Span::styled(
+                    format!("({} nearly full) ", warnings),
+                    Style::default().fg(Color::Red),
+                ),
+            ])
+        } else {
+            Line::from(" Filesystems ")
+        };
+
+        let lines: Vec<Line> = sorted
+            .iter()
+            .map(|fs| {
+                let color = if fs.warning { Color::Red } else { Color::Green };
+                // Inodes are only worth showing when they run out before space
+                let inodes = if fs.inodes_used_percent > fs.used_percent {
+                    format!(" i{:.0}%", fs.inodes_used_percent)
+                } else {
+                    String::new()
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:>3.0}%{} ", fs.used_percent, inodes),
+                        Style::default().fg(color),
+                    ),
+                    // Free space before the mount point, which may be cut off
+                    Span::raw(format!(
+                        "{:>9} free  {}",
+                        format_bytes(fs.free_bytes),
+                        fs.mount_point
+                    )),
+                ])
+            })
+            .collect();
+
+        let paragraph =
+            Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn render_network_info(&self, frame: &mut Frame, area: Rect) {
+        // Split area into 2 sections: Receive, Transmit throughput
+        let net_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+
+        if let Some(ref network) = self.state.current_network {
+            let rx: u64 = network.iter().map(|n| n.rx_bytes_per_sec).sum();
+            let tx: u64 = network.iter().map(|n| n.tx_bytes_per_sec).sum();
+            let errors: f32 = network
+                .iter()
+                .map(|n| n.rx_errors_per_sec + n.tx_errors_per_sec)
+                .sum();
+            let drops: f32 = network
+                .iter()
+                .map(|n| n.rx_drops_per_sec + n.tx_drops_per_sec)
+                .sum();
+
+            // Scale both graphs to the busiest recent value, at least 128 KB/s
+            let max_rate = self
+                .state
+                .net_rx_history
+                .iter()
+                .chain(self.state.net_tx_history.iter())
+                .copied()
+                .max()
+                .unwrap_or(0)
+                .max(128 * 1024);
+
+            let rx_graph = TimeGraph::new(&self.state.net_rx_history)
+                .max(max_rate)
+                .style(Style::default().fg(Color::Green))
+                .block(
+                    Block::default()
+                        .title(format!(" Net RX: {} ", format_bytes_rate(rx)))
+                        .borders(Borders::ALL),
+                );
+            frame.render_widget(rx_graph, net_chunks[0]);
+
+            let tx_graph = TimeGraph::new(&self.state.net_tx_history)
+                .max(max_rate)
+                .style(Style::default().fg(Color::Blue))
+                .block(
+                    Block::default()
+                        .title(if errors > 0.0 || drops > 0.0 {
+                            format!(
+                                " Net TX: {} | Err {:.0}/s Drop {:.0}/s ",
+                                format_bytes_rate(tx),
+                                errors,
+                                drops
+                            )
+                        } else {
+                            format!(" Net TX: {} ", format_bytes_rate(tx))
+                        })
+                        .borders(Borders::ALL),
+                );
+            frame.render_widget(tx_graph, net_chunks[1]);
+        } else {
+            let no_data = Paragraph::new(
+                self.state
+                    .not_available("Network metrics", metrics::Subsystem::Network),
+            )
+            .block(Block::default().title(" Network ").borders(Borders::ALL));
+            frame.render_widget(no_data, area);
+        }
+    }
+
     fn render_cpu_graph(&self, frame: &mut Frame, area: Rect) {
         let current_usage = if let Some(ref perf) = self.state.current_performance {
             ((perf.ecpu_usage.1 + perf.pcpu_usage.1) / 2.0 * 100.0) as u64
+        } else if let Some(ref cpu_time) = self.state.current_cpu_time {
+            cpu_time.total.busy() as u64
         } else {
             0
         };
 
+        // Tick-based split, comparable with the `top` CPU line
+        let breakdown = self
+            .state
+            .current_cpu_time
+            .as_ref()
+            .map(|cpu_time| {
+                let t = &cpu_time.total;
+                let mut parts = vec![
+                    format!("us {:.1}", t.user),
+                    format!("sy {:.1}", t.system),
+                    format!("ni {:.1}", t.nice),
+                ];
+                if let Some(iowait) = t.iowait {
+                    parts.push(format!("wa {:.1}", iowait));
+                }
+                if let (Some(irq), Some(softirq)) = (t.irq, t.softirq) {
+                    parts.push(format!("irq {:.1}", irq + softirq));
+                }
+                if let Some(steal) = t.steal {
+                    parts.push(format!("st {:.1}", steal));
+                }
+                format!("[{}] ", parts.join(" "))
+            })
+            .unwrap_or_default();
+
         let graph = TimeGraph::new(&self.state.cpu_usage_history)
             .max(100)
             .style(Style::default().fg(Color::Cyan))
             .block(
                 Block::default()
-                    .title(format!(" CPU Usage: {}% ", current_usage))
+                    .title(format!(" CPU Usage: {}% {}", current_usage, breakdown))
                     .borders(Borders::ALL),
             );
 
@@ -559,6 +1616,14 @@ impl Dashboard {
             .split(area);
 
         if let Some(ref perf) = self.state.current_performance {
+            let throttle_tag = |cluster: &str| {
+                if self.state.throttling(cluster).is_some() {
+                    " [THROTTLED]"
+                } else {
+                    ""
+                }
+            };
+
             // E-CPU Frequency Graph
             let ecpu_graph = TimeGraph::new(&self.state.ecpu_freq_history)
                 .max(4000) // 4000 MHz max
@@ -566,9 +1631,11 @@ impl Dashboard {
                 .block(
                     Block::default()
                         .title(format!(
-                            " E-CPU: {} MHz ({:.0}%) ",
+                            " E-CPU: {} MHz ({:.0}%){}{} ",
                             perf.ecpu_usage.0,
-                            perf.ecpu_usage.1 * 100.0
+                            perf.ecpu_usage.1 * 100.0,
+                            format_cluster_freqs(&perf.clusters, 'E'),
+                            throttle_tag("ECPU")
                         ))
                         .borders(Borders::ALL),
                 );
@@ -581,9 +1648,11 @@ impl Dashboard {
                 .block(
                     Block::default()
                         .title(format!(
-                            " P-CPU: {} MHz ({:.0}%) ",
+                            " P-CPU: {} MHz ({:.0}%){}{} ",
                             perf.pcpu_usage.0,
-                            perf.pcpu_usage.1 * 100.0
+                            perf.pcpu_usage.1 * 100.0,
+                            format_cluster_freqs(&perf.clusters, 'P'),
+                            throttle_tag("PCPU")
                         ))
                         .borders(Borders::ALL),
                 );
@@ -604,7 +1673,11 @@ impl Dashboard {
                 );
             frame.render_widget(gpu_graph, freq_chunks[2]);
         } else {
-            let no_data = Paragraph::new("Frequency data not available").block(
+            let no_data = Paragraph::new(
+                self.state
+                    .not_available("Frequency data", metrics::Subsystem::Performance),
+            )
+            .block(
                 Block::default()
                     .title(" Frequencies ")
                     .borders(Borders::ALL),
@@ -613,3 +1686,562 @@ impl Dashboard {
         }
     }
 }
+
+// " (E0:2 P0:4 P1:4)" on chips with more than one cluster of a kind, plus the die count
+fn format_cluster_layout(clusters: &[metrics::CpuCluster]) -> String {
+    if clusters.len() <= 2 {
+        return String::new();
+    }
+    let layout: Vec<String> = clusters
+        .iter()
+        .map(|c| format!("{}:{}", c.id, c.cores))
+        .collect();
+    let dies = clusters.iter().map(|c| c.die + 1).max().unwrap_or(1);
+    if dies > 1 {
+        format!(" ({}, {} dies)", layout.join(" "), dies)
+    } else {
+        format!(" ({})", layout.join(" "))
+    }
+}
+
+// " [P0 2400, P1 2100]" when there is more than one cluster of the kind, empty otherwise
+fn format_cluster_freqs(clusters: &[metrics::ClusterSample], kind: char) -> String {
+    let freqs: Vec<String> = clusters
+        .iter()
+        .filter(|c| c.id.starts_with(kind))
+        .map(|c| format!("{} {}", c.id, c.freq_mhz))
+        .collect();
+    if freqs.len() > 1 {
+        format!(" [{}]", freqs.join(", "))
+    } else {
+        String::new()
+    }
+}
+
+fn format_battery(battery: &metrics::BatteryMetrics) -> String {
+    let mut text = format!("{:.0}%", battery.charge_percent);
+    if battery.charging {
+        text.push_str(&format!(", charging {:.1}W", battery.power_watts));
+        if let Some(minutes) = battery.time_to_full_min {
+            text.push_str(&format!(", full in {}h{:02}m", minutes / 60, minutes % 60));
+        }
+    } else if battery.external_connected {
+        text.push_str(", on AC");
+    } else {
+        text.push_str(&format!(", discharging {:.1}W", -battery.power_watts));
+        if let Some(minutes) = battery.time_to_empty_min {
+            text.push_str(&format!(", {}h{:02}m left", minutes / 60, minutes % 60));
+        }
+    }
+    if let Some(watts) = battery.adapter_watts {
+        text.push_str(&format!(" ({}W adapter)", watts));
+    }
+    text
+}
+
+fn format_bytes_rate(bytes_per_sec: u64) -> String {
+    let value = bytes_per_sec as f64;
+    if value >= 1_073_741_824.0 {
+        format!("{:.1} GB/s", value / 1_073_741_824.0)
+    } else if value >= 1_048_576.0 {
+        format!("{:.1} MB/s", value / 1_048_576.0)
+    } else {
+        format!("{:.1} KB/s", value / 1024.0)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let value = bytes as f64;
+    if value >= 1_073_741_824.0 {
+        format!("{:.1} GB", value / 1_073_741_824.0)
+    } else if value >= 1_048_576.0 {
+        format!("{:.1} MB", value / 1_048_576.0)
+    } else {
+        format!("{:.0} KB", value / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::pressure::{Pressure, PressureStall};
+    use ratatui::backend::TestBackend;
+    use std::collections::VecDeque;
+    use std::path::PathBuf;
+
+    // Replays a fixed list of updates, one per poll
+    struct ScriptedSource {
+        updates: VecDeque<MetricData>,
+        interval: Duration,
+    }
+
+    impl MetricSource for ScriptedSource {
+        fn cpu_metrics(&mut self) -> Result<metrics::CpuMetrics, metrics::AtopError> {
+            Ok(metrics::CpuMetrics {
+                physical_cores: 10,
+                logical_cores: 10,
+                cpu_brand: "Apple M1 Pro".to_string(),
+                cpu_frequency_mhz: 3228,
+                chip_name: Some("Apple M1 Pro".to_string()),
+                ecpu_cores: Some(2),
+                pcpu_cores: Some(8),
+                ecpu_freqs_mhz: None,
+                pcpu_freqs_mhz: None,
+                clusters: vec![
+                    cluster("E0", 2, true),
+                    cluster("P0", 4, false),
+                    cluster("P1", 4, false),
+                ],
+            })
+        }
+
+        fn poll(&mut self) -> Option<MetricData> {
+            self.updates.pop_front()
+        }
+
+        fn interval(&self) -> Duration {
+            self.interval
+        }
+
+        fn set_interval(&mut self, interval: Duration) {
+            self.interval = interval;
+        }
+    }
+
+    const GB: u64 = 1024 * 1024 * 1024;
+    const MB: u64 = 1024 * 1024;
+
+    fn stall(avg10: f32) -> Pressure {
+        Pressure {
+            some: PressureStall {
+                avg10,
+                avg60: avg10 / 2.0,
+                avg300: avg10 / 4.0,
+                total_us: 1_000_000,
+            },
+            full: Some(PressureStall::default()),
+        }
+    }
+
+    fn cluster(id: &str, cores: u32, efficiency: bool) -> metrics::CpuCluster {
+        metrics::CpuCluster {
+            id: id.to_string(),
+            efficiency,
+            cores,
+            ..Default::default()
+        }
+    }
+
+    fn process(
+        pid: u32,
+        ppid: u32,
+        name: &str,
+        cpu_percent: f32,
+        rss_mb: u64,
+    ) -> metrics::ProcessMetrics {
+        metrics::ProcessMetrics {
+            pid,
+            ppid,
+            name: name.to_string(),
+            command: format!("/usr/bin/{}", name),
+            user: if pid < 100 { "root" } else { "ci" }.to_string(),
+            cpu_percent,
+            rss_bytes: rss_mb * MB,
+            threads: 4,
+            state: "sleeping".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn cgroup(
+        path: &str,
+        processes: u32,
+        cpu_percent: f32,
+        memory_mb: u64,
+    ) -> metrics::CgroupMetrics {
+        metrics::CgroupMetrics {
+            path: path.to_string(),
+            processes,
+            cpu_percent,
+            memory_current: memory_mb * MB,
+            memory_anon: memory_mb * MB / 2,
+            memory_file: memory_mb * MB / 4,
+            memory_pressure: Some(stall(cpu_percent / 10.0)),
+            ..Default::default()
+        }
+    }
+
+    // Deterministic update number `step`; values ramp so graphs have a shape
+    fn sample(step: u32) -> MetricData {
+        let s = step as f32;
+        MetricData {
+            memory: memory::MemoryMetrics {
+                total: 18 * GB,
+                ram_total: 16 * GB,
+                ram_usage: 8 * GB + step as u64 * 256 * MB,
+                swap_total: 2 * GB,
+                swap_usage: 512 * MB,
+                breakdown: memory::MemoryBreakdown {
+                    active: 5 * GB,
+                    inactive: 3 * GB,
+                    wired: 2 * GB,
+                    compressed: GB,
+                    free: 5 * GB,
+                    ..Default::default()
+                },
+                activity: memory::MemoryActivity {
+                    pageins_per_sec: 12.0,
+                    pageouts_per_sec: 3.0,
+                    ..Default::default()
+                },
+                pressure: metrics::MemoryPressure::Warning,
+            },
+            power: Some(metrics::PowerMetrics {
+                cpu_power: 2.0 + s * 0.5,
+                ecpu_power: 0.5,
+                pcpu_power: 1.5 + s * 0.5,
+                gpu_power: 1.0 + s * 0.25,
+                ane_power: 0.0,
+                ram_power: 0.4,
+                gpu_ram_power: 0.1,
+                all_power: 3.0 + s * 0.75,
+                sys_power: 9.5,
+            }),
+            performance: Some(metrics::PerformanceSample {
+                ecpu_usage: (1200 + step * 100, 0.2 + s * 0.05),
+                pcpu_usage: (2000 + step * 200, 0.3 + s * 0.1),
+                gpu_usage: (400 + step * 50, 0.1),
+                ecpu_active: 0.2,
+                pcpu_active: 0.3,
+                clusters: vec![
+                    metrics::ClusterSample {
+                        id: "E0".to_string(),
+                        freq_mhz: 1200 + step * 100,
+                        utilization: 0.2 + s * 0.05,
+                        active: 0.2,
+                    },
+                    metrics::ClusterSample {
+                        id: "P0".to_string(),
+                        freq_mhz: 2100 + step * 200,
+                        utilization: 0.35 + s * 0.1,
+                        active: 0.35,
+                    },
+                    metrics::ClusterSample {
+                        id: "P1".to_string(),
+                        freq_mhz: 1900 + step * 200,
+                        utilization: 0.25 + s * 0.1,
+                        active: 0.25,
+                    },
+                ],
+            }),
+            throttling: None,
+            battery: Some(metrics::BatteryMetrics {
+                charge_percent: 76.0,
+                power_watts: -9.5,
+                time_to_empty_min: Some(312),
+                ..Default::default()
+            }),
+            disks: Some(vec![metrics::DiskMetrics {
+                name: "disk0".to_string(),
+                read_bytes_per_sec: step as u64 * 4 * MB,
+                write_bytes_per_sec: 2 * MB,
+                read_iops: 120.0,
+                write_iops: 40.0,
+                busy_percent: 12.0,
+            }]),
+            network: Some(vec![metrics::NetworkMetrics {
+                name: "en0".to_string(),
+                rx_bytes_per_sec: 3 * MB,
+                tx_bytes_per_sec: step as u64 * 256 * 1024,
+                ..Default::default()
+            }]),
+            processes: Some(vec![
+                process(1, 0, "launchd", 0.5, 20),
+                process(310, 1, "cargo", 12.0, 180),
+                process(311, 310, "rustc", 96.5, 1400),
+                process(312, 310, "rustc", 88.0, 900),
+                process(420, 1, "WindowServer", 7.5, 300),
+            ]),
+            cgroups: Some(vec![
+                cgroup("/", 0, 180.0, 4096),
+                cgroup("/ci.slice", 0, 170.0, 2600),
+                cgroup("/ci.slice/job-1", 3, 150.0, 2400),
+                cgroup("/ci.slice/job-2", 1, 20.0, 200),
+                cgroup("/system.slice", 12, 10.0, 800),
+            ]),
+            pressure: Some(metrics::PressureMetrics {
+                cpu: Some(stall(35.0)),
+                memory: Some(stall(12.5)),
+                io: Some(stall(4.0)),
+                memory_level: metrics::MemoryPressure::Warning,
+            }),
+            load: Some(metrics::LoadMetrics {
+                load_1: 6.5,
+                load_5: 4.25,
+                load_15: 3.0,
+                context_switches_per_sec: Some(15200.0),
+                interrupts_per_sec: Some(4100.0),
+                forks_per_sec: Some(14.0),
+                running_tasks: Some(7),
+                blocked_tasks: Some(1),
+            }),
+            cpu_time: None,
+            filesystems: Some(vec![
+                metrics::FilesystemMetrics {
+                    mount_point: "/".to_string(),
+                    device: "/dev/disk3s1".to_string(),
+                    fs_type: "apfs".to_string(),
+                    total_bytes: 500 * GB,
+                    used_bytes: 470 * GB,
+                    free_bytes: 30 * GB,
+                    used_percent: 94.0,
+                    inodes_used_percent: 2.0,
+                    warning: true,
+                    ..Default::default()
+                },
+                metrics::FilesystemMetrics {
+                    mount_point: "/Volumes/Builds".to_string(),
+                    device: "/dev/disk5s1".to_string(),
+                    fs_type: "apfs".to_string(),
+                    total_bytes: 1000 * GB,
+                    used_bytes: 400 * GB,
+                    free_bytes: 600 * GB,
+                    used_percent: 40.0,
+                    inodes_used_percent: 1.0,
+                    ..Default::default()
+                },
+            ]),
+            unavailable: Vec::new(),
+        }
+    }
+
+    fn dashboard(steps: u32) -> Dashboard<ScriptedSource> {
+        let source = ScriptedSource {
+            updates: (0..steps).map(sample).collect(),
+            interval: Duration::from_millis(1000),
+        };
+        let mut dashboard = Dashboard::with_source(source).unwrap();
+        while let Some(data) = dashboard.source.poll() {
+            dashboard.state.update(data);
+        }
+        dashboard
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    fn render(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> Terminal<TestBackend> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(draw).unwrap();
+        terminal
+    }
+
+    // Compares the rendered text with src/ui/snapshots/<name>.txt;
+    // run with UPDATE_SNAPSHOTS=1 to (re)write the files
+    fn assert_snapshot(name: &str, terminal: &Terminal<TestBackend>) {
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                text.push_str(buffer[(x, y)].symbol());
+            }
+            text.push('\n');
+        }
+
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/ui/snapshots")
+            .join(format!("{}.txt", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &text).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "missing snapshot {}, run with UPDATE_SNAPSHOTS=1",
+                path.display()
+            )
+        });
+        assert_eq!(text, expected, "snapshot {} differs", name);
+    }
+
+    #[test]
+    fn renders_cpu_info() {
+        let dashboard = dashboard(3);
+        let terminal = render(100, 7, |f| dashboard.render_cpu_info(f, f.area()));
+        assert_snapshot("cpu_info", &terminal);
+    }
+
+    #[test]
+    fn renders_power_graphs() {
+        let dashboard = dashboard(8);
+        let terminal = render(120, 8, |f| dashboard.render_power_info(f, f.area()));
+        assert_snapshot("power_info", &terminal);
+    }
+
+    #[test]
+    fn explains_missing_power() {
+        let mut dashboard = dashboard(1);
+        dashboard.state.update(MetricData {
+            power: None,
+            unavailable: vec![metrics::AtopError::Unsupported {
+                subsystem: metrics::Subsystem::Power,
+            }],
+            ..sample(1)
+        });
+        let terminal = render(80, 4, |f| dashboard.render_power_info(f, f.area()));
+        assert_snapshot("power_unavailable", &terminal);
+    }
+
+    #[test]
+    fn explains_missing_cgroups_with_the_configured_root() {
+        let mut dashboard = dashboard(1);
+        dashboard.state.update(MetricData {
+            cgroups: None,
+            unavailable: vec![metrics::AtopError::unavailable(
+                metrics::Subsystem::Cgroup,
+                "/tmp/custom-root is not a cgroup v2 hierarchy",
+            )],
+            ..sample(1)
+        });
+        let terminal = render(80, 3, |f| dashboard.render_cgroup_table(f, f.area()));
+        assert_snapshot("cgroup_unavailable", &terminal);
+    }
+
+    #[test]
+    fn renders_frequency_graphs() {
+        let dashboard = dashboard(8);
+        let terminal = render(120, 8, |f| dashboard.render_frequency_graphs(f, f.area()));
+        assert_snapshot("frequency_graphs", &terminal);
+    }
+
+    #[test]
+    fn renders_memory_and_pressure() {
+        let dashboard = dashboard(8);
+        let terminal = render(140, 8, |f| dashboard.render_memory_info(f, f.area()));
+        assert_snapshot("memory_info", &terminal);
+    }
+
+    #[test]
+    fn renders_synthetic_scenario() {
+        let source = CollectorSource::new(crate::SamplerOptions {
+            interval: Duration::from_millis(100),
+            scenario: Some(metrics::Scenario::default()),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut dashboard = Dashboard::with_source(source).unwrap();
+        let data = loop {
+            match dashboard.source.poll() {
+                Some(data) => break data,
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        dashboard.state.update(data);
+        let mut terminal = Terminal::new(TestBackend::new(160, 90)).unwrap();
+        dashboard.draw(&mut terminal).unwrap();
+        assert_snapshot("synthetic", &terminal);
+    }
+
+    #[test]
+    fn renders_full_dashboard() {
+        let mut dashboard = dashboard(8);
+        let mut terminal = Terminal::new(TestBackend::new(160, 90)).unwrap();
+        dashboard.draw(&mut terminal).unwrap();
+        assert_snapshot("dashboard", &terminal);
+    }
+
+    #[test]
+    fn renders_dashboard_on_a_normal_terminal() {
+        let mut dashboard = dashboard(8);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        dashboard.draw(&mut terminal).unwrap();
+        assert_snapshot("dashboard_120x40", &terminal);
+    }
+
+    #[test]
+    fn short_terminals_collapse_lower_panels_first() {
+        let panels =
+            |height| -> Vec<Panel> { panel_layout(height).into_iter().map(|(p, _)| p).collect() };
+        let total = |height| {
+            panel_layout(height)
+                .iter()
+                .map(|(_, rows)| rows)
+                .sum::<u16>()
+        };
+
+        // Tall enough: every panel at full height, the table gets the rest
+        assert_eq!(panels(86), Panel::ALL);
+        assert!(
+            panel_layout(86)
+                .iter()
+                .filter(|(p, _)| p.is_graph())
+                .all(|&(_, rows)| rows == GRAPH_HEIGHT)
+        );
+        assert_eq!(total(86), 62);
+
+        // About 40 rows: the lower panels go, the core graphs shrink and the table keeps its rows
+        assert_eq!(
+            panels(36),
+            [Panel::CpuInfo, Panel::CpuGraph, Panel::Memory, Panel::Power]
+        );
+        assert_eq!(total(36), 36 - MIN_TABLE_HEIGHT);
+
+        assert!(panels(MIN_TABLE_HEIGHT + 4).is_empty());
+    }
+
+    #[test]
+    fn filters_and_selects_processes() {
+        let mut dashboard = dashboard(1);
+        dashboard.handle_key(key(KeyCode::Char('/')));
+        for c in "name:rustc".chars() {
+            dashboard.handle_key(key(KeyCode::Char(c)));
+        }
+        dashboard.handle_key(key(KeyCode::Enter));
+        dashboard.handle_key(key(KeyCode::Down));
+        dashboard.handle_key(key(KeyCode::Down));
+
+        assert_eq!(dashboard.state.process_view.selected(), Some(312));
+        let terminal = render(160, 8, |f| dashboard.render_process_table(f, f.area()));
+        assert_snapshot("process_filter", &terminal);
+    }
+
+    #[test]
+    fn shows_process_tree() {
+        let mut dashboard = dashboard(1);
+        dashboard.handle_key(key(KeyCode::Char('t')));
+        let terminal = render(160, 9, |f| dashboard.render_process_table(f, f.area()));
+        assert_snapshot("process_tree", &terminal);
+    }
+
+    #[test]
+    fn toggles_cgroup_view() {
+        let mut dashboard = dashboard(1);
+        dashboard.handle_key(key(KeyCode::Char('g')));
+        dashboard.handle_key(key(KeyCode::Down));
+        dashboard.handle_key(key(KeyCode::Down));
+        dashboard.handle_key(key(KeyCode::Right));
+        assert!(dashboard.state.group_view);
+
+        let terminal = render(160, 9, |f| dashboard.render_cgroup_table(f, f.area()));
+        assert_snapshot("cgroup_view", &terminal);
+
+        // Process keys are ignored while the group table is shown
+        dashboard.handle_key(key(KeyCode::Char('t')));
+        assert!(!dashboard.state.process_view.tree);
+        dashboard.handle_key(key(KeyCode::Char('g')));
+        assert!(!dashboard.state.group_view);
+    }
+
+    #[test]
+    fn changes_refresh_interval() {
+        let mut dashboard = dashboard(0);
+        dashboard.handle_key(key(KeyCode::Char('+')));
+        dashboard.handle_key(key(KeyCode::Char('+')));
+        assert_eq!(dashboard.source.interval(), Duration::from_millis(1200));
+        dashboard.handle_key(key(KeyCode::Char('-')));
+        assert_eq!(dashboard.source.interval(), Duration::from_millis(1100));
+        assert!(dashboard.handle_key(key(KeyCode::Char('q'))));
+    }
+}
@@ -1,4 +1,7 @@
+pub mod cgroup_view;
 pub mod dashboard;
+pub mod process_view;
+pub mod source;
 pub mod time_graph;
 
 pub use dashboard::Dashboard;
@@ -0,0 +1,305 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::metrics::{ProcessFilter, ProcessMetrics};
+
+// Columns of the process table, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSort {
+    Pid,
+    Name,
+    User,
+    Cpu,
+    Memory,
+    Threads,
+    Nice,
+    State,
+    Energy,
+    DiskRead,
+    DiskWrite,
+}
+
+impl ProcessSort {
+    pub const ALL: [ProcessSort; 11] = [
+        ProcessSort::Pid,
+        ProcessSort::Name,
+        ProcessSort::User,
+        ProcessSort::Cpu,
+        ProcessSort::Memory,
+        ProcessSort::Threads,
+        ProcessSort::Nice,
+        ProcessSort::State,
+        ProcessSort::Energy,
+        ProcessSort::DiskRead,
+        ProcessSort::DiskWrite,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ProcessSort::Pid => "PID",
+            ProcessSort::Name => "Name",
+            ProcessSort::User => "User",
+            ProcessSort::Cpu => "CPU%",
+            ProcessSort::Memory => "RSS",
+            ProcessSort::Threads => "Thr",
+            ProcessSort::Nice => "Ni",
+            ProcessSort::State => "State",
+            ProcessSort::Energy => "Power",
+            ProcessSort::DiskRead => "Read/s",
+            ProcessSort::DiskWrite => "Write/s",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&c| c == self).unwrap_or(0)
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    // Ascending order for this column
+    fn compare(self, a: &ProcessMetrics, b: &ProcessMetrics) -> Ordering {
+        match self {
+            ProcessSort::Pid => a.pid.cmp(&b.pid),
+            ProcessSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProcessSort::User => a.user.cmp(&b.user),
+            ProcessSort::Cpu => a.cpu_percent.total_cmp(&b.cpu_percent),
+            ProcessSort::Memory => a.rss_bytes.cmp(&b.rss_bytes),
+            ProcessSort::Threads => a.threads.cmp(&b.threads),
+            ProcessSort::Nice => a.nice.cmp(&b.nice),
+            ProcessSort::State => a.state.cmp(&b.state),
+            ProcessSort::Energy => a
+                .energy_watts
+                .unwrap_or(0.0)
+                .total_cmp(&b.energy_watts.unwrap_or(0.0)),
+            ProcessSort::DiskRead => a.disk_read_bytes_per_sec.cmp(&b.disk_read_bytes_per_sec),
+            ProcessSort::DiskWrite => a.disk_write_bytes_per_sec.cmp(&b.disk_write_bytes_per_sec),
+        }
+    }
+}
+
+/// A visible line of the process table
+pub struct ProcessRow<'a> {
+    pub process: &'a ProcessMetrics,
+    pub depth: usize, // nesting level in tree view
+    pub has_children: bool,
+    pub collapsed: bool,
+    pub pinned: bool,
+}
+
+/// Sorting, filtering, tree and selection state of the process table
+pub struct ProcessView {
+    pub sort: ProcessSort,
+    pub sort_desc: bool,
+    pub tree: bool,
+    pub editing: bool, // typing a filter expression
+    query: String,
+    filter: ProcessFilter,
+    collapsed: HashSet<u32>,
+    pinned: HashSet<u32>,
+    selected: Option<u32>, // selected PID, so it survives re-sorting
+}
+
+impl ProcessView {
+    pub fn new() -> Self {
+        Self {
+            sort: ProcessSort::Cpu,
+            sort_desc: true,
+            tree: false,
+            editing: false,
+            query: String::new(),
+            filter: ProcessFilter::default(),
+            collapsed: HashSet::new(),
+            pinned: HashSet::new(),
+            selected: None,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn push_query(&mut self, c: char) {
+        self.query.push(c);
+        self.filter = ProcessFilter::parse(&self.query);
+    }
+
+    pub fn pop_query(&mut self) {
+        self.query.pop();
+        self.filter = ProcessFilter::parse(&self.query);
+    }
+
+    pub fn clear_query(&mut self) {
+        self.query.clear();
+        self.filter = ProcessFilter::default();
+    }
+
+    pub fn selected(&self) -> Option<u32> {
+        self.selected
+    }
+
+    pub fn toggle_pin(&mut self) {
+        if let Some(pid) = self.selected
+            && !self.pinned.remove(&pid)
+        {
+            self.pinned.insert(pid);
+        }
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        if let Some(pid) = self.selected {
+            if collapsed {
+                self.collapsed.insert(pid);
+            } else {
+                self.collapsed.remove(&pid);
+            }
+        }
+    }
+
+    pub fn toggle_collapsed(&mut self) {
+        if let Some(pid) = self.selected {
+            let collapsed = self.collapsed.contains(&pid);
+            self.set_collapsed(!collapsed);
+        }
+    }
+
+    /// Move the selection by `delta` rows, clamped to the visible rows
+    pub fn move_selection(&mut self, processes: &[ProcessMetrics], delta: isize) {
+        let rows = self.rows(processes);
+        if rows.is_empty() {
+            self.selected = None;
+            return;
+        }
+        let index = match self.selected_index(&rows) {
+            Some(index) => (index as isize + delta).clamp(0, rows.len() as isize - 1) as usize,
+            None => 0,
+        };
+        self.selected = Some(rows[index].process.pid);
+    }
+
+    pub fn selected_index(&self, rows: &[ProcessRow]) -> Option<usize> {
+        let pid = self.selected?;
+        rows.iter().position(|row| row.process.pid == pid)
+    }
+
+    /// Visible rows: pinned processes first, then the filtered list or tree
+    pub fn rows<'a>(&self, processes: &'a [ProcessMetrics]) -> Vec<ProcessRow<'a>> {
+        let order = |a: &&ProcessMetrics, b: &&ProcessMetrics| {
+            let order = self.sort.compare(a, b);
+            if self.sort_desc {
+                order.reverse()
+            } else {
+                order
+            }
+        };
+
+        // Pinned processes stay visible regardless of the filter
+        let mut pinned: Vec<&ProcessMetrics> = processes
+            .iter()
+            .filter(|p| self.pinned.contains(&p.pid))
+            .collect();
+        pinned.sort_by(order);
+        let mut rows: Vec<ProcessRow> = pinned
+            .into_iter()
+            .map(|process| ProcessRow {
+                process,
+                depth: 0,
+                has_children: false,
+                collapsed: false,
+                pinned: true,
+            })
+            .collect();
+
+        let rest: Vec<&ProcessMetrics> = processes
+            .iter()
+            .filter(|p| !self.pinned.contains(&p.pid))
+            .collect();
+
+        if !self.tree {
+            let mut matching: Vec<&ProcessMetrics> = rest
+                .into_iter()
+                .filter(|p| self.filter.matches(p))
+                .collect();
+            matching.sort_by(order);
+            rows.extend(matching.into_iter().map(|process| ProcessRow {
+                process,
+                depth: 0,
+                has_children: false,
+                collapsed: false,
+                pinned: false,
+            }));
+            return rows;
+        }
+
+        // Processes whose parent is not listed (or is themselves) become roots
+        let pids: HashSet<u32> = rest.iter().map(|p| p.pid).collect();
+        let mut children: HashMap<u32, Vec<&ProcessMetrics>> = HashMap::new();
+        let mut roots = Vec::new();
+        for process in rest {
+            if process.ppid != process.pid && pids.contains(&process.ppid) {
+                children.entry(process.ppid).or_default().push(process);
+            } else {
+                roots.push(process);
+            }
+        }
+        roots.sort_by(order);
+        for list in children.values_mut() {
+            list.sort_by(order);
+        }
+
+        // With a filter, keep ancestors of matching processes for context
+        let mut visible = HashMap::new();
+        for root in &roots {
+            self.mark_visible(root, &children, &mut visible);
+        }
+
+        let mut stack: Vec<(&ProcessMetrics, usize)> =
+            roots.iter().rev().map(|&root| (root, 0)).collect();
+        while let Some((process, depth)) = stack.pop() {
+            if !visible.get(&process.pid).copied().unwrap_or(false) {
+                continue;
+            }
+            let kids = children.get(&process.pid);
+            let collapsed = self.collapsed.contains(&process.pid);
+            rows.push(ProcessRow {
+                process,
+                depth,
+                has_children: kids.is_some(),
+                collapsed,
+                pinned: false,
+            });
+            if !collapsed && let Some(kids) = kids {
+                stack.extend(kids.iter().rev().map(|&kid| (kid, depth + 1)));
+            }
+        }
+
+        rows
+    }
+
+    fn mark_visible(
+        &self,
+        process: &ProcessMetrics,
+        children: &HashMap<u32, Vec<&ProcessMetrics>>,
+        visible: &mut HashMap<u32, bool>,
+    ) -> bool {
+        let mut shown = self.filter.is_empty() || self.filter.matches(process);
+        if let Some(kids) = children.get(&process.pid) {
+            for kid in kids {
+                shown |= self.mark_visible(kid, children, visible);
+            }
+        }
+        visible.insert(process.pid, shown);
+        shown
+    }
+}
+
+impl Default for ProcessView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use crate::metrics::{self, AtopError, memory};
+use crate::stream::{BackgroundSampler, Backpressure, Subscription};
+use crate::{SamplerOptions, SystemMetrics};
+
+/// One round of metrics delivered to the dashboard
+#[derive(Debug, Default, Clone)]
+pub struct MetricData {
+    pub memory: memory::MemoryMetrics,
+    pub power: Option<metrics::PowerMetrics>,
+    pub performance: Option<metrics::PerformanceSample>,
+    pub throttling: Option<Vec<metrics::ClusterThrottle>>,
+    pub battery: Option<metrics::BatteryMetrics>,
+    pub disks: Option<Vec<metrics::DiskMetrics>>,
+    pub network: Option<Vec<metrics::NetworkMetrics>>,
+    pub processes: Option<Vec<metrics::ProcessMetrics>>,
+    pub cgroups: Option<Vec<metrics::CgroupMetrics>>,
+    pub pressure: Option<metrics::PressureMetrics>,
+    pub load: Option<metrics::LoadMetrics>,
+    pub cpu_time: Option<metrics::CpuTimeMetrics>,
+    pub filesystems: Option<Vec<metrics::FilesystemMetrics>>,
+    pub unavailable: Vec<AtopError>, // why the subsystems left as None have no data
+}
+
+impl From<SystemMetrics> for MetricData {
+    fn from(m: SystemMetrics) -> Self {
+        let performance = match (m.ecpu_usage, m.pcpu_usage, m.gpu_usage) {
+            (Some(ecpu_usage), Some(pcpu_usage), Some(gpu_usage)) => {
+                let clusters = m.clusters.unwrap_or_default();
+                // Share of time active per kind, weighted by the cores of each cluster
+                let active = |efficiency: bool| {
+                    let (busy, cores) = m
+                        .cpu
+                        .clusters
+                        .iter()
+                        .zip(&clusters)
+                        .filter(|(layout, _)| layout.efficiency == efficiency)
+                        .fold((0.0, 0), |(busy, cores), (layout, sample)| {
+                            (
+                                busy + sample.active * layout.cores as f32,
+                                cores + layout.cores,
+                            )
+                        });
+                    if cores > 0 { busy / cores as f32 } else { 0.0 }
+                };
+                Some(metrics::PerformanceSample {
+                    ecpu_usage,
+                    pcpu_usage,
+                    gpu_usage,
+                    ecpu_active: active(true),
+                    pcpu_active: active(false),
+                    clusters,
+                })
+            }
+            _ => None,
+        };
+
+        MetricData {
+            memory: m.memory,
+            power: m.power,
+            performance,
+            throttling: m.throttling,
+            battery: m.battery,
+            disks: m.disks,
+            network: m.network,
+            processes: m.processes,
+            cgroups: m.cgroups,
+            pressure: m.pressure,
+            load: m.load,
+            cpu_time: m.cpu_time,
+            filesystems: m.filesystems,
+            unavailable: m.unavailable,
+        }
+    }
+}
+
+/// Where the dashboard gets its data from
+pub trait MetricSource {
+    /// Static CPU description, read once at startup
+    fn cpu_metrics(&mut self) -> Result<metrics::CpuMetrics, AtopError>;
+
+    /// Next pending update, if any; must not block
+    fn poll(&mut self) -> Option<MetricData>;
+
+    fn interval(&self) -> Duration;
+
+    /// Called when the user changes the refresh rate
+    fn set_interval(&mut self, interval: Duration);
+}
+
+/// Samples the system (or the scenario in `options`) on a `BackgroundSampler` thread
+pub struct CollectorSource {
+    sampler: BackgroundSampler,
+    updates: Subscription<Result<SystemMetrics, AtopError>>,
+}
+
+impl CollectorSource {
+    pub fn new(options: SamplerOptions) -> Result<Self, AtopError> {
+        let sampler = BackgroundSampler::spawn(options)?;
+        // A full queue discards the new update; the UI will get the next fresh one
+        let updates = sampler.subscribe(10, Backpressure::DropNewest);
+        Ok(Self { sampler, updates })
+    }
+}
+
+impl MetricSource for CollectorSource {
+    fn cpu_metrics(&mut self) -> Result<metrics::CpuMetrics, AtopError> {
+        Ok(self.sampler.cpu_metrics().clone())
+    }
+
+    fn poll(&mut self) -> Option<MetricData> {
+        // A failed sample (memory couldn't be read) is skipped; the next one may succeed
+        loop {
+            match self.updates.try_recv() {
+                Ok(Ok(metrics)) => return Some(metrics.into()),
+                Ok(Err(_)) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        self.sampler.interval()
+    }
+
+    fn set_interval(&mut self, interval: Duration) {
+        self.sampler.set_interval(interval);
+    }
+}
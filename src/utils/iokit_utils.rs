@@ -5,8 +5,15 @@ use core_foundation::array::CFArrayRef;
 use core_foundation::base::TCFType;
 use core_foundation::data::CFDataRef;
 use core_foundation::dictionary::CFDictionaryRef;
-use core_foundation::string::{CFString, CFStringGetCString, CFStringRef, kCFStringEncodingUTF8};
-use core_foundation_sys::dictionary::CFDictionaryGetValue;
+use core_foundation::string::{
+    CFString, CFStringGetCString, CFStringGetTypeID, CFStringRef, kCFStringEncodingUTF8,
+};
+use core_foundation_sys::base::{CFGetTypeID, CFTypeRef};
+use core_foundation_sys::dictionary::{CFDictionaryGetTypeID, CFDictionaryGetValue};
+use core_foundation_sys::number::{
+    CFBooleanGetTypeID, CFBooleanGetValue, CFBooleanRef, CFNumberGetTypeID, CFNumberGetValue,
+    CFNumberRef, kCFNumberSInt64Type,
+};
 
 /// Create a CoreFoundation string from a Rust &str (owned CFString)
 pub fn cf_string(val: &str) -> CFString {
@@ -68,6 +75,67 @@ pub fn cf_dict_get_data(
     }
 }
 
+/// Get a nested CFDictionary value from a CFDictionary by key. Returns None if missing.
+pub fn cf_dict_get_dict(dict: CFDictionaryRef, key: &str) -> Option<CFDictionaryRef> {
+    let val = cf_dict_get_value(dict, key)?;
+    unsafe {
+        if CFGetTypeID(val) == CFDictionaryGetTypeID() {
+            Some(val as CFDictionaryRef)
+        } else {
+            None
+        }
+    }
+}
+
+/// Get a CFNumber value from a CFDictionary by key as i64. Returns None if missing.
+pub fn cf_dict_get_i64(dict: CFDictionaryRef, key: &str) -> Option<i64> {
+    let val = cf_dict_get_value(dict, key)?;
+    unsafe {
+        if CFGetTypeID(val) != CFNumberGetTypeID() {
+            return None;
+        }
+        let mut out: i64 = 0;
+        let ok = CFNumberGetValue(
+            val as CFNumberRef,
+            kCFNumberSInt64Type,
+            &mut out as *mut i64 as *mut std::ffi::c_void,
+        );
+        if ok { Some(out) } else { None }
+    }
+}
+
+/// Get a CFString value from a CFDictionary by key. Returns None if missing.
+pub fn cf_dict_get_string(dict: CFDictionaryRef, key: &str) -> Option<String> {
+    let val = cf_dict_get_value(dict, key)?;
+    unsafe {
+        if CFGetTypeID(val) == CFStringGetTypeID() {
+            Some(cf_string_to_rust(val as CFStringRef))
+        } else {
+            None
+        }
+    }
+}
+
+/// Get a CFBoolean value from a CFDictionary by key. Returns None if missing.
+pub fn cf_dict_get_bool(dict: CFDictionaryRef, key: &str) -> Option<bool> {
+    let val = cf_dict_get_value(dict, key)?;
+    unsafe {
+        if CFGetTypeID(val) == CFBooleanGetTypeID() {
+            Some(CFBooleanGetValue(val as CFBooleanRef))
+        } else {
+            None
+        }
+    }
+}
+
+fn cf_dict_get_value(dict: CFDictionaryRef, key: &str) -> Option<CFTypeRef> {
+    unsafe {
+        let k = CFString::new(key);
+        let val = CFDictionaryGetValue(dict, k.as_CFTypeRef());
+        if val.is_null() { None } else { Some(val) }
+    }
+}
+
 // ===== sysctl helpers =====
 use std::ffi::CString;
 
@@ -1 +1,4 @@
+#[cfg(target_os = "macos")]
 pub mod iokit_utils;
+#[cfg(target_os = "linux")]
+pub mod procfs;
@@ -0,0 +1,54 @@
+// Utilities: procfs/sysfs readers used by the Linux collectors
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Read a procfs/sysfs file as a string with surrounding whitespace trimmed
+pub fn read_trimmed(path: impl AsRef<Path>) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Read a procfs/sysfs file containing a single unsigned integer
+pub fn read_u64(path: impl AsRef<Path>) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+/// Read a procfs/sysfs file containing a single signed integer
+pub fn read_i64(path: impl AsRef<Path>) -> Option<i64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+/// Parse `Key: value [kB]` lines (as in /proc/meminfo) into a map.
+/// Values with a `kB` suffix are converted to bytes.
+pub fn parse_key_values(content: &str) -> HashMap<String, u64> {
+    let mut map = HashMap::new();
+    for line in content.lines() {
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = rest.split_whitespace();
+        let Some(value) = parts.next().and_then(|v| v.parse::<u64>().ok()) else {
+            continue;
+        };
+        let value = match parts.next() {
+            Some("kB") => value * 1024,
+            _ => value,
+        };
+        map.insert(key.trim().to_string(), value);
+    }
+    map
+}
+
+/// Write `files` (relative path, contents) into a fresh directory under the system temp dir,
+/// for tests that point a reader at a fake procfs/sysfs/cgroupfs root
+#[cfg(test)]
+pub fn fixture_dir(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let root = std::env::temp_dir().join(format!("atop-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, contents) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    root
+}